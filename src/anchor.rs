@@ -10,7 +10,7 @@ pub struct AnchorManager {
 
 impl AnchorManager {
     pub fn build(doc: &MakefileDoc) -> Self {
-        let mut allocator = AnchorAllocator::new();
+        let mut allocator = IdAllocator::new('-', slugify);
         let mut category_ids = Vec::with_capacity(doc.categories.len());
         let mut command_ids = Vec::with_capacity(doc.categories.len());
 
@@ -41,26 +41,33 @@ impl AnchorManager {
     }
 }
 
-struct AnchorAllocator {
+/// Hands out unique `{prefix}{separator}{name}` ids, appending a counter on
+/// collisions. `sanitize` turns a raw name into the characters allowed in an
+/// id, the separator included.
+pub(crate) struct IdAllocator {
     used_ids: HashSet<String>,
+    separator: char,
+    sanitize: fn(&str) -> String,
 }
 
-impl AnchorAllocator {
-    fn new() -> Self {
+impl IdAllocator {
+    pub(crate) fn new(separator: char, sanitize: fn(&str) -> String) -> Self {
         Self {
             used_ids: HashSet::new(),
+            separator,
+            sanitize,
         }
     }
 
-    fn next_id(&mut self, prefix: &str, raw: &str) -> String {
-        let base = slugify(raw);
+    pub(crate) fn next_id(&mut self, prefix: &str, raw: &str) -> String {
+        let base = (self.sanitize)(raw);
         let base = if base.is_empty() {
             String::from("item")
         } else {
             base
         };
 
-        let base_id = format!("{}-{}", prefix, base);
+        let base_id = format!("{}{}{}", prefix, self.separator, base);
 
         if self.used_ids.insert(base_id.clone()) {
             return base_id;
@@ -69,7 +76,7 @@ impl AnchorAllocator {
         let mut suffix = 2;
 
         loop {
-            let candidate = format!("{}-{}", base_id, suffix);
+            let candidate = format!("{}{}{}", base_id, self.separator, suffix);
             if self.used_ids.insert(candidate.clone()) {
                 return candidate;
             }
@@ -108,6 +115,7 @@ pub fn slugify(text: &str) -> String {
 mod tests {
     use super::*;
     use crate::model::{Category, Command, MakefileDoc};
+    use std::collections::BTreeMap;
 
     #[test]
     fn slugify_normalizes_separators() {
@@ -132,7 +140,12 @@ mod tests {
                         name: String::from("build"),
                         description: String::new(),
                         dependencies: vec![],
+                        prerequisites: vec![],
                         env: vec![],
+                        conditions: vec![],
+                        branches: vec![],
+                        variables: vec![],
+                        tags: BTreeMap::new(),
                     }],
                 },
                 Category {
@@ -142,13 +155,23 @@ mod tests {
                             name: String::from("build"),
                             description: String::new(),
                             dependencies: vec![],
+                            prerequisites: vec![],
                             env: vec![],
+                            conditions: vec![],
+                            branches: vec![],
+                            variables: vec![],
+                            tags: BTreeMap::new(),
                         },
                         Command {
                             name: String::from("build-2"),
                             description: String::new(),
                             dependencies: vec![],
+                            prerequisites: vec![],
                             env: vec![],
+                            conditions: vec![],
+                            branches: vec![],
+                            variables: vec![],
+                            tags: BTreeMap::new(),
                         },
                     ],
                 },
@@ -157,6 +180,8 @@ mod tests {
                     commands: vec![],
                 },
             ],
+            patterns: vec![],
+            targets: vec![],
         };
 
         let anchors = AnchorManager::build(&doc);
@@ -0,0 +1,211 @@
+use crate::{config::Config, model::MakefileDoc, render::Renderer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// A completion script for `make`, offering the documented targets and the
+/// variables they need.
+pub struct CompletionRenderer {
+    pub shell: Shell,
+}
+
+impl Renderer for CompletionRenderer {
+    fn render(&self, doc: &MakefileDoc, _config: &Config) -> String {
+        let candidates = candidates(doc);
+
+        match self.shell {
+            Shell::Bash => render_bash(&candidates),
+            Shell::Zsh => render_zsh(&candidates),
+            Shell::Fish => render_fish(&candidates),
+        }
+    }
+}
+
+struct Candidates {
+    targets: Vec<(String, String)>,
+    variables: Vec<(String, String)>,
+}
+
+fn candidates(doc: &MakefileDoc) -> Candidates {
+    let targets = doc
+        .commands()
+        .map(|cmd| {
+            let hint = cmd.description.split("\\n").next().unwrap_or_default();
+            (cmd.name.clone(), hint.trim().to_string())
+        })
+        .collect();
+
+    let mut required_by: Vec<(String, Vec<&str>)> = Vec::new();
+    for cmd in doc.commands() {
+        let params = cmd.params().into_iter().map(|(name, _)| name);
+
+        for name in cmd.env.iter().map(String::as_str).chain(params) {
+            match required_by.iter_mut().find(|(var, _)| var == name) {
+                Some((_, commands)) if commands.last() == Some(&cmd.name.as_str()) => {}
+                Some((_, commands)) => commands.push(&cmd.name),
+                None => required_by.push((name.to_string(), vec![&cmd.name])),
+            }
+        }
+    }
+
+    let variables = required_by
+        .into_iter()
+        .map(|(name, commands)| {
+            (
+                format!("{}=", name),
+                format!("Used by {}", commands.join(", ")),
+            )
+        })
+        .collect();
+
+    Candidates { targets, variables }
+}
+
+fn render_bash(candidates: &Candidates) -> String {
+    let mut script = String::new();
+
+    script.push_str("# bash completion for make, generated by makefile2doc\n");
+    script.push_str("_makefile2doc_make() {\n");
+    script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    script.push_str("    local candidates=(\n");
+    for (name, hint) in candidates.targets.iter().chain(&candidates.variables) {
+        script.push_str(&format!(
+            "        {}\n",
+            sh_quote(&format!("{}:{}", name, hint))
+        ));
+    }
+    script.push_str("    )\n");
+    script.push_str(concat!(
+        "    local entry hint i\n",
+        "    COMPREPLY=()\n",
+        "    for entry in \"${candidates[@]}\"; do\n",
+        "        [[ ${entry%%:*} == \"$cur\"* ]] && COMPREPLY+=(\"$entry\")\n",
+        "    done\n",
+        "    if (( ${#COMPREPLY[@]} == 1 )); then\n",
+        "        COMPREPLY=(\"${COMPREPLY[0]%%:*}\")\n",
+        "        [[ ${COMPREPLY[0]} == *= ]] && compopt -o nospace\n",
+        "    else\n",
+        "        for i in \"${!COMPREPLY[@]}\"; do\n",
+        "            entry=\"${COMPREPLY[$i]}\"\n",
+        "            hint=\"${entry#*:}\"\n",
+        "            COMPREPLY[$i]=\"${entry%%:*}${hint:+  -- $hint}\"\n",
+        "        done\n",
+        "    fi\n",
+        "}\n",
+        "complete -F _makefile2doc_make make\n",
+    ));
+
+    script
+}
+
+fn render_zsh(candidates: &Candidates) -> String {
+    let mut script = String::new();
+
+    script.push_str("# zsh completion for make, generated by makefile2doc\n");
+    script.push_str("_makefile2doc_make() {\n");
+    script.push_str("    local -a targets variables\n");
+    for (array, entries) in [
+        ("targets", &candidates.targets),
+        ("variables", &candidates.variables),
+    ] {
+        script.push_str(&format!("    {}=(\n", array));
+        for (name, hint) in entries {
+            script.push_str(&format!(
+                "        {}\n",
+                sh_quote(&format!("{}:{}", name.replace(':', "\\:"), hint))
+            ));
+        }
+        script.push_str("    )\n");
+    }
+    script.push_str("    _describe -t targets 'make target' targets\n");
+    script.push_str("    _describe -t variables 'variable' variables -S ''\n");
+    script.push_str("}\n");
+    script.push_str("compdef _makefile2doc_make make\n");
+
+    script
+}
+
+fn render_fish(candidates: &Candidates) -> String {
+    let mut script = String::new();
+
+    script.push_str("# fish completion for make, generated by makefile2doc\n");
+    for (name, hint) in candidates.targets.iter().chain(&candidates.variables) {
+        script.push_str(&format!("complete -c make -f -a {}", fish_quote(name)));
+        if !hint.is_empty() {
+            script.push_str(&format!(" -d {}", fish_quote(hint)));
+        }
+        script.push('\n');
+    }
+
+    script
+}
+
+fn sh_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    fn doc() -> MakefileDoc {
+        parse(
+            r#"
+                ## @description Run the migrations\nSecond line
+                ## @env DATABASE_URL
+                migrate:
+
+                ## @description Don't seed in prod
+                ## @env DATABASE_URL, SEED
+                ## @param VERSION the version to seed, SEED
+                seed:
+            "#,
+        )
+    }
+
+    #[test]
+    fn variables_list_the_commands_using_them() {
+        let candidates = candidates(&doc());
+
+        assert_eq!(
+            candidates.targets,
+            vec![
+                (String::from("migrate"), String::from("Run the migrations")),
+                (String::from("seed"), String::from("Don't seed in prod")),
+            ]
+        );
+        assert_eq!(
+            candidates.variables,
+            vec![
+                (
+                    String::from("DATABASE_URL="),
+                    String::from("Used by migrate, seed")
+                ),
+                (String::from("SEED="), String::from("Used by seed")),
+                (String::from("VERSION="), String::from("Used by seed")),
+            ]
+        );
+    }
+
+    #[test]
+    fn quotes_hints_for_each_shell() {
+        let render = |shell| CompletionRenderer { shell }.render(&doc(), &Config::default());
+
+        assert!(render(Shell::Bash).contains("        'seed:Don'\\''t seed in prod'\n"));
+        assert!(render(Shell::Bash).ends_with("complete -F _makefile2doc_make make\n"));
+        assert!(render(Shell::Zsh).contains("        'SEED=:Used by seed'\n"));
+        assert!(
+            render(Shell::Fish)
+                .contains("complete -c make -f -a 'seed' -d 'Don\\'t seed in prod'\n")
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+pub const CONFIG_FILE_NAME: &str = "makefile2doc.toml";
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub comment_marker: String,
+    pub tag_sigil: String,
+    pub tags: Vec<CustomTag>,
+    pub strict: bool,
+    pub focus_depth: Option<usize>,
+    pub transitive_reduction: bool,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomTag {
+    pub name: String,
+    #[serde(default)]
+    pub list: bool,
+    pub title: Option<String>,
+}
+
+impl CustomTag {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
+
+pub const BUILTIN_TAGS: [&str; 5] = ["category", "description", "depends", "env", "param"];
+
+/// Built-in list tag whose entries start with a variable name, completed as a
+/// `VAR=` argument like `@env`.
+pub const PARAM_TAG: &str = "param";
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            comment_marker: String::from("##"),
+            tag_sigil: String::from("@"),
+            tags: Vec::new(),
+            strict: false,
+            focus_depth: None,
+            transitive_reduction: false,
+            project: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let config: Self = toml::from_str(content).map_err(|e| e.message().to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        // Only comment lines reach the parser, so a marker like `//` would
+        // silently document nothing.
+        if !self.comment_marker.starts_with('#') {
+            return Err(format!(
+                "The comment marker '{}' must start with '#'",
+                self.comment_marker
+            ));
+        }
+        for (i, tag) in self.tags.iter().enumerate() {
+            if !tag
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                || tag.name.is_empty()
+            {
+                return Err(format!("Invalid custom tag name '{}'", tag.name));
+            }
+            if BUILTIN_TAGS.contains(&tag.name.as_str()) {
+                return Err(format!("The tag '{}' is built-in", tag.name));
+            }
+            if self.tags[..i].iter().any(|t| t.name == tag.name) {
+                return Err(format!("The tag '{}' is declared multiple times", tag.name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::from_toml(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_fall_back_to_defaults() {
+        let config = Config::from_toml("tag_sigil = \":\"").unwrap();
+
+        assert_eq!(config.comment_marker, "##");
+        assert_eq!(config.tag_sigil, ":");
+    }
+
+    #[test]
+    fn parses_custom_tags_in_declaration_order() {
+        let config = Config::from_toml(
+            r#"
+            [[tags]]
+            name = "owner"
+            title = "Owner"
+
+            [[tags]]
+            name = "links"
+            list = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.tags.len(), 2);
+        assert_eq!(config.tags[0].title(), "Owner");
+        assert!(!config.tags[0].list);
+        assert_eq!(config.tags[1].title(), "links");
+        assert!(config.tags[1].list);
+    }
+
+    #[test]
+    fn rejects_invalid_custom_tags() {
+        assert!(Config::from_toml("[[tags]]\nname = \"env\"").is_err());
+        assert!(Config::from_toml("[[tags]]\nname = \"a b\"").is_err());
+        assert!(Config::from_toml("[[tags]]\nname = \"a\"\n[[tags]]\nname = \"a\"").is_err());
+    }
+
+    #[test]
+    fn rejects_comment_markers_that_are_not_comments() {
+        assert!(Config::from_toml("comment_marker = \"//\"").is_err());
+        assert!(Config::from_toml("comment_marker = \"\"").is_err());
+        assert!(Config::from_toml("comment_marker = \"#:\"").is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(Config::from_toml("comment_marke = \"#\"").is_err());
+    }
+}
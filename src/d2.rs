@@ -0,0 +1,104 @@
+use crate::{
+    config::Config,
+    model::MakefileDoc,
+    render::Renderer,
+    workflow::{Node, NodeKind, WorkflowGraph, quote},
+};
+
+/// The workflow graph as a D2 diagram, one dashed container per category.
+pub struct D2Renderer;
+
+impl Renderer for D2Renderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        let graph = WorkflowGraph::build(doc, config);
+        let mut d2 = String::new();
+
+        d2.push_str("direction: right\n");
+
+        for group in &graph.groups {
+            d2.push_str(&format!("\n{}: {} {{\n", group.id, quote(group.label)));
+            d2.push_str("  style.fill: transparent\n");
+            d2.push_str("  style.stroke-dash: 3\n");
+            for &idx in &group.nodes {
+                let node = &graph.nodes[idx];
+                d2.push_str(&format!("  {}: {} {{\n", node.id, quote(node.label)));
+                if let NodeKind::Pattern(_) = node.kind {
+                    d2.push_str("    shape: hexagon\n");
+                }
+                d2.push_str(&format!("    style.fill: \"{}\"\n", group.colors.fill));
+                d2.push_str(&format!("    style.stroke: \"{}\"\n", group.colors.stroke));
+                d2.push_str("  }\n");
+            }
+            d2.push_str("}\n");
+        }
+
+        d2.push('\n');
+        for node in graph.ungrouped() {
+            d2.push_str(&format!("{}: {}\n", node.id, quote(node.label)));
+        }
+        for &(from, to) in &graph.edges {
+            d2.push_str(&format!(
+                "{} -> {}\n",
+                path(&graph, &graph.nodes[from]),
+                path(&graph, &graph.nodes[to])
+            ));
+        }
+
+        d2
+    }
+}
+
+/// Nodes nested in a container are referenced through it.
+fn path(graph: &WorkflowGraph, node: &Node) -> String {
+    match node.group {
+        Some(group) => format!("{}.{}", graph.groups[group].id, node.id),
+        None => node.id.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn references_nested_nodes_through_their_container() {
+        let doc = parse(
+            r#"
+                ## @category Build
+                ## @description Build
+                ## @depends vendor
+                build:
+            "#,
+        );
+
+        let d2 = D2Renderer.render(&doc, &Config::default());
+
+        assert!(d2.starts_with("direction: right\n"));
+        assert!(d2.contains(concat!(
+            "cat_Build: \"Build\" {\n",
+            "  style.fill: transparent\n",
+            "  style.stroke-dash: 3\n",
+            "  cmd_build: \"build\" {\n",
+            "    style.fill: \"#E1F5FE\"\n",
+            "    style.stroke: \"#01579B\"\n",
+            "  }\n",
+            "}\n",
+        )));
+        assert!(d2.ends_with("cmd_vendor: \"vendor\"\ncat_Build.cmd_build -> cmd_vendor\n"));
+    }
+
+    #[test]
+    fn pattern_rules_are_hexagons() {
+        let doc = parse(
+            r#"
+                ## @description Compile a PDF
+                %.pdf: %.md
+            "#,
+        );
+
+        let d2 = D2Renderer.render(&doc, &Config::default());
+
+        assert!(d2.contains("  pattern_0: \"%.pdf\" {\n    shape: hexagon\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DiagnosticKind {
+    DuplicateCategory,
+    UnknownTag,
+    OrphanAnnotation,
+    UnknownDependency,
+    UndocumentedDependency,
+    DependencyCycle,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub severity: Severity,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(kind: DiagnosticKind, line: Option<usize>, message: String) -> Self {
+        Self {
+            kind,
+            severity: Severity::Warning,
+            line,
+            message,
+        }
+    }
+
+    pub fn error(kind: DiagnosticKind, line: Option<usize>, message: String) -> Self {
+        Self {
+            kind,
+            severity: Severity::Error,
+            line,
+            message,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        };
+
+        match self.line {
+            Some(line) => write!(f, "{} (line {}): {}", label, line, self.message),
+            None => write!(f, "{}: {}", label, self.message),
+        }
+    }
+}
+
+pub fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let threshold = (name.chars().count() / 3).max(2);
+
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("descripton", "description"), 1);
+        assert_eq!(edit_distance("depend", "depends"), 1);
+        assert_eq!(edit_distance("env", "env"), 0);
+        assert_eq!(edit_distance("", "env"), 3);
+    }
+
+    #[test]
+    fn suggest_picks_the_closest_candidate() {
+        let known = ["category", "description", "depends", "env"];
+
+        assert_eq!(suggest("descripton", &known), Some("description"));
+        assert_eq!(suggest("depend", &known), Some("depends"));
+        assert_eq!(suggest("envs", &known), Some("env"));
+        assert_eq!(suggest("owner", &known), None);
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    config::Config,
+    model::MakefileDoc,
+    render::Renderer,
+    workflow::{NodeKind, WorkflowGraph, quote},
+};
+
+/// The workflow graph as a Graphviz digraph, one cluster per category.
+pub struct DotRenderer;
+
+impl Renderer for DotRenderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        let graph = WorkflowGraph::build(doc, config);
+        let mut dot = String::new();
+
+        dot.push_str("digraph workflow {\n");
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str("    node [shape=box, style=\"rounded,filled\", fillcolor=\"#FFFFFF\", fontcolor=\"#000000\", penwidth=2];\n");
+
+        for group in &graph.groups {
+            dot.push_str(&format!("\n    subgraph cluster_{} {{\n", group.id));
+            dot.push_str(&format!("        label={};\n", quote(group.label)));
+            dot.push_str("        style=dashed;\n");
+            dot.push_str(&format!(
+                "        node [fillcolor=\"{}\", color=\"{}\"];\n",
+                group.colors.fill, group.colors.stroke
+            ));
+            for &idx in &group.nodes {
+                let node = &graph.nodes[idx];
+                let shape = match node.kind {
+                    NodeKind::Pattern(_) => ", shape=hexagon, style=filled",
+                    _ => "",
+                };
+                dot.push_str(&format!(
+                    "        {} [label={}{}];\n",
+                    node.id,
+                    quote(node.label),
+                    shape
+                ));
+            }
+            dot.push_str("    }\n");
+        }
+
+        dot.push('\n');
+        for node in graph.ungrouped() {
+            dot.push_str(&format!("    {} [label={}];\n", node.id, quote(node.label)));
+        }
+        for &(from, to) in &graph.edges {
+            dot.push_str(&format!(
+                "    {} -> {};\n",
+                graph.nodes[from].id, graph.nodes[to].id
+            ));
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn renders_clusters_and_hexagon_patterns() {
+        let doc = parse(
+            r#"
+                ## @category Build "all"
+                ## @description Build
+                ## @depends docs.pdf
+                build:
+
+                ## @description Compile a PDF
+                %.pdf: %.md
+            "#,
+        );
+
+        let dot = DotRenderer.render(&doc, &Config::default());
+
+        assert!(dot.starts_with("digraph workflow {\n    rankdir=LR;\n"));
+        assert!(dot.contains(concat!(
+            "    subgraph cluster_cat_Build_all {\n",
+            "        label=\"Build \\\"all\\\"\";\n",
+            "        style=dashed;\n",
+            "        node [fillcolor=\"#E1F5FE\", color=\"#01579B\"];\n",
+            "        cmd_build [label=\"build\"];\n",
+            "    }\n",
+        )));
+        assert!(
+            dot.contains("        pattern_0 [label=\"%.pdf\", shape=hexagon, style=filled];\n")
+        );
+        assert!(dot.ends_with("    cmd_build -> pattern_0;\n}\n"));
+    }
+}
@@ -1,7 +1,30 @@
-use crate::{anchor::AnchorManager, model::MakefileDoc};
+use crate::{
+    anchor::AnchorManager,
+    config::Config,
+    graph::DependencyGraph,
+    lexer::{Condition, conjunction},
+    mermaid::label,
+    model::{Command, MakefileDoc, PatternRule, TagValue, Variable},
+    render::Renderer,
+    workflow::{Node, NodeKind, WorkflowGraph},
+};
+
+pub struct MarkdownRenderer;
+
+impl Renderer for MarkdownRenderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        generate_with(doc, config)
+    }
+}
 
 pub fn generate(doc: &MakefileDoc) -> String {
+    generate_with(doc, &Config::default())
+}
+
+pub fn generate_with(doc: &MakefileDoc, config: &Config) -> String {
     let anchors = AnchorManager::build(doc);
+    let graph = DependencyGraph::build(doc);
+    let workflow = WorkflowGraph::build(doc, config);
     let mut md = String::new();
 
     md.push_str("# Makefile Documentation\n");
@@ -14,11 +37,20 @@ pub fn generate(doc: &MakefileDoc) -> String {
 
     md.push_str("\n---\n\n");
 
-    md.push_str(&generate_workflow_graph(doc));
+    md.push_str(&generate_run_order(doc, &anchors, &graph));
 
     md.push_str("\n---\n\n");
 
-    md.push_str(&generate_section_details(doc, &anchors));
+    md.push_str(&generate_workflow_graph(doc, &workflow));
+
+    md.push_str("\n---\n\n");
+
+    md.push_str(&generate_section_details(doc, &anchors, &workflow, config));
+
+    if !doc.patterns.is_empty() {
+        md.push_str("\n---\n\n");
+        md.push_str(&generate_pattern_rules(doc));
+    }
     md
 }
 
@@ -44,60 +76,124 @@ fn generate_cheat_sheet(doc: &MakefileDoc, anchors: &AnchorManager) -> String {
     section
 }
 
-fn generate_workflow_graph(doc: &MakefileDoc) -> String {
+fn generate_run_order(
+    doc: &MakefileDoc,
+    anchors: &AnchorManager,
+    graph: &DependencyGraph,
+) -> String {
+    let mut section = String::new();
+
+    section.push_str("## Run Order\n");
+    section.push_str("| Command | Runs (in order) |\n");
+    section.push_str("| :--- | :--- |\n");
+
+    for (cat_idx, cat) in doc.categories.iter().enumerate() {
+        for (cmd_idx, cmd) in cat.commands.iter().enumerate() {
+            let Some(node) = graph.index_of(&cmd.name) else {
+                continue;
+            };
+
+            let chain = graph
+                .run_order(node)
+                .into_iter()
+                .map(|idx| format!("`{}`", graph.name(idx)))
+                .collect::<Vec<_>>()
+                .join(" → ");
+
+            section.push_str(&format!(
+                "| [`make {}`](#{}) | {} |\n",
+                cmd.name,
+                anchors.command_id(cat_idx, cmd_idx),
+                chain
+            ));
+        }
+    }
+    section
+}
+
+fn generate_workflow_graph(doc: &MakefileDoc, workflow: &WorkflowGraph) -> String {
     let mut section = String::new();
     section.push_str("## Workflow Graph\n");
     section.push_str("```mermaid\n");
-    section.push_str("flowchart LR\n");
+    section.push_str(&flowchart(doc, workflow));
+    section.push_str("```\n");
+    section
+}
 
-    for (i, cat) in doc.categories.iter().enumerate() {
-        let safe_cat_name = cat.name.replace(" ", "_");
+/// The Mermaid source of the workflow graph, without any Markdown fence.
+pub(crate) fn workflow_flowchart(doc: &MakefileDoc, config: &Config) -> String {
+    flowchart(doc, &WorkflowGraph::build(doc, config))
+}
 
-        section.push_str(&format!("    subgraph {}[{}]\n", safe_cat_name, cat.name));
+/// The Mermaid source of a workflow graph or of a part of it. Groups left
+/// without nodes are skipped.
+fn flowchart(doc: &MakefileDoc, graph: &WorkflowGraph) -> String {
+    let mut section = String::new();
+    section.push_str("flowchart LR\n");
 
-        for cmd in &cat.commands {
-            section.push_str(&format!("        {}({})\n", cmd.name, cmd.name));
+    for (i, group) in graph.groups.iter().enumerate() {
+        if group.nodes.is_empty() {
+            continue;
         }
+        let class = if i < doc.categories.len() {
+            format!("cat{}", i)
+        } else {
+            String::from("patternRule")
+        };
 
+        section.push_str(&format!(
+            "    subgraph {}[{}]\n",
+            group.id,
+            label(group.label)
+        ));
+        for &idx in &group.nodes {
+            section.push_str(&format!("        {}\n", mermaid_node(&graph.nodes[idx])));
+        }
         section.push_str("    end\n");
 
         section.push_str(&format!(
             "    style {} fill:transparent,stroke-dasharray: 5 5\n",
-            safe_cat_name
+            group.id
         ));
-
-        let colors = [
-            "#E1F5FE", "#E8F5E9", "#FFF3E0", "#F3E5F5", "#FFEBEE", "#ECEFF1",
-        ];
-        let stroke_colors = [
-            "#01579B", "#1B5E20", "#E65100", "#4A148C", "#B71C1C", "#263238",
-        ];
-        let color_idx = i % colors.len();
-
         section.push_str(&format!(
-            "    classDef cat{} fill:{},stroke:{},stroke-width:2px,color:#000;\n",
-            i, colors[color_idx], stroke_colors[color_idx]
+            "    classDef {} fill:{},stroke:{},stroke-width:2px,color:#000;\n",
+            class, group.colors.fill, group.colors.stroke
         ));
-
-        for cmd in &cat.commands {
-            section.push_str(&format!("    class {} cat{}\n", cmd.name, i));
+        for &idx in &group.nodes {
+            section.push_str(&format!("    class {} {}\n", graph.nodes[idx].id, class));
         }
     }
 
+    for node in graph.ungrouped() {
+        section.push_str(&format!("    {}\n", mermaid_node(node)));
+    }
+
     section.push('\n');
-    for cat in &doc.categories {
-        for cmd in &cat.commands {
-            for dep in &cmd.dependencies {
-                section.push_str(&format!("    {} --> {}\n", cmd.name, dep));
-            }
-        }
+    for &(from, to) in &graph.edges {
+        section.push_str(&format!(
+            "    {} --> {}\n",
+            graph.nodes[from].id, graph.nodes[to].id
+        ));
     }
 
-    section.push_str("```\n");
     section
 }
 
-fn generate_section_details(doc: &MakefileDoc, anchors: &AnchorManager) -> String {
+fn mermaid_node(node: &Node) -> String {
+    match node.kind {
+        NodeKind::Pattern(_) => format!("{}{{{{{}}}}}", node.id, label(node.label)),
+        _ => format!("{}({})", node.id, label(node.label)),
+    }
+}
+
+fn generate_section_details(
+    doc: &MakefileDoc,
+    anchors: &AnchorManager,
+    workflow: &WorkflowGraph,
+    config: &Config,
+) -> String {
+    let custom_tags = &config.tags;
+    let has_params = doc.commands().any(|cmd| !cmd.params().is_empty());
     let mut section = String::new();
 
     section.push_str("## Section Details\n");
@@ -108,22 +204,179 @@ fn generate_section_details(doc: &MakefileDoc, anchors: &AnchorManager) -> Strin
             "\n<a id=\"{}\"></a>\n### {}\n",
             category_anchor, cat.name
         ));
-        section.push_str("| Command | Description | Dependencies | Required Variables |\n");
-        section.push_str("| :--- | :--- | :--- | :--- |\n");
+        section.push_str("| Command | Description | Dependencies | Used by | Required Variables |");
+        if has_params {
+            section.push_str(" Parameters |");
+        }
+        for tag in custom_tags {
+            section.push_str(&format!(" {} |", tag.title()));
+        }
+        section.push_str("\n| :--- | :--- | :--- | :--- | :--- |");
+        if has_params {
+            section.push_str(" :--- |");
+        }
+        section.push_str(&" :--- |".repeat(custom_tags.len()));
+        section.push('\n');
 
         for (cmd_idx, cmd) in cat.commands.iter().enumerate() {
             let name = format_name(&cmd.name, anchors.command_id(cat_idx, cmd_idx));
-            let desc = format_description(&cmd.description);
+            let desc = format_branches(cmd)
+                + &format_conditions(&cmd.conditions)
+                + &format_variables(&cmd.variables);
             let deps = format_list(&cmd.dependencies);
+            let used_by = format_used_by(doc, anchors, &cmd.name);
             let envs = format_list(&cmd.env);
 
-            section.push_str(&format!("| {} | {} | {} | {} |\n", name, desc, deps, envs));
+            section.push_str(&format!(
+                "| {} | {} | {} | {} | {} |",
+                name, desc, deps, used_by, envs
+            ));
+            if has_params {
+                section.push_str(&format!(" {} |", format_params(cmd)));
+            }
+            for tag in custom_tags {
+                section.push_str(&format!(" {} |", format_tag(cmd.tags.get(&tag.name))));
+            }
+            section.push('\n');
+        }
+
+        if let Some(depth) = config.focus_depth {
+            for cmd in &cat.commands {
+                section.push_str(&generate_focused_graph(doc, workflow, &cmd.name, depth));
+            }
         }
     }
 
     section
 }
 
+fn generate_focused_graph(
+    doc: &MakefileDoc,
+    workflow: &WorkflowGraph,
+    name: &str,
+    depth: usize,
+) -> String {
+    let mut section = String::new();
+
+    let Some(node) = workflow.find_command(name) else {
+        return section;
+    };
+    let focused = workflow.neighborhood(node, depth);
+    if focused.edges.is_empty() {
+        return section;
+    }
+
+    section.push_str(&format!(
+        "\n<details>\n<summary>Dependency graph of <code>make {}</code></summary>\n\n",
+        name
+    ));
+    section.push_str("```mermaid\n");
+    section.push_str(&flowchart(doc, &focused));
+    section.push_str(&format!(
+        "    style {} stroke-width:4px,font-weight:bold\n",
+        workflow.nodes[node].id
+    ));
+
+    section.push_str("```\n\n</details>\n");
+    section
+}
+
+fn generate_pattern_rules(doc: &MakefileDoc) -> String {
+    let mut section = String::new();
+
+    section.push_str("## Pattern Rules\n");
+    section.push_str("| Pattern | Prerequisites | Description | Example | Required Variables |\n");
+    section.push_str("| :--- | :--- | :--- | :--- | :--- |\n");
+
+    for pattern in &doc.patterns {
+        let name = match &pattern.targets {
+            Some(targets) => format!("`{}` (for `{}`)", pattern.pattern, targets),
+            None => format!("`{}`", pattern.pattern),
+        };
+        let prereqs = format_list(&pattern.prerequisites);
+        let desc = format_description(&pattern.description);
+        let example = format_stem_example(pattern);
+        let envs = format_list(&pattern.env);
+
+        section.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            name, prereqs, desc, example, envs
+        ));
+    }
+
+    section
+}
+
+fn format_stem_example(pattern: &PatternRule) -> String {
+    let (target, sources) = stem_example(pattern);
+    let sources: Vec<String> = sources.iter().map(|s| format!("`{}`", s)).collect();
+
+    if sources.is_empty() {
+        format!("`make {}`", target)
+    } else {
+        format!("`make {}` from {}", target, sources.join(", "))
+    }
+}
+
+/// A target built by the pattern rule, taken from its static targets when
+/// possible, and the pattern prerequisites it is made from.
+pub(crate) fn stem_example(pattern: &PatternRule) -> (String, Vec<String>) {
+    let (prefix, suffix) = pattern.pattern.split_once('%').unwrap_or(("", ""));
+
+    let stem = pattern
+        .targets
+        .iter()
+        .flat_map(|targets| targets.split_whitespace())
+        .filter(|target| !target.contains('$') && pattern.matches(target))
+        .map(|target| &target[prefix.len()..target.len() - suffix.len()])
+        .next()
+        .unwrap_or("example");
+
+    let target = PatternRule::apply(&pattern.pattern, stem);
+    let sources = pattern
+        .prerequisites
+        .iter()
+        .filter(|p| p.contains('%'))
+        .map(|p| PatternRule::apply(p, stem))
+        .collect();
+
+    (target, sources)
+}
+
+fn format_used_by(doc: &MakefileDoc, anchors: &AnchorManager, name: &str) -> String {
+    let links: Vec<String> = doc
+        .dependents(name)
+        .map(
+            |dependent| match command_anchor(doc, anchors, &dependent.name) {
+                Some(anchor) => format!("[`{}`](#{})", dependent.name, anchor),
+                None => format!("`{}`", dependent.name),
+            },
+        )
+        .collect();
+
+    if links.is_empty() {
+        "-".to_string()
+    } else {
+        links.join(", ")
+    }
+}
+
+pub(crate) fn command_anchor<'a>(
+    doc: &MakefileDoc,
+    anchors: &'a AnchorManager,
+    name: &str,
+) -> Option<&'a str> {
+    doc.categories
+        .iter()
+        .enumerate()
+        .find_map(|(cat_idx, cat)| {
+            cat.commands
+                .iter()
+                .position(|cmd| cmd.name == name)
+                .map(|cmd_idx| anchors.command_id(cat_idx, cmd_idx))
+        })
+}
+
 fn format_name(cmd: &str, anchor_id: &str) -> String {
     if cmd.is_empty() {
         "-".to_string()
@@ -140,6 +393,81 @@ fn format_description(desc: &str) -> String {
     }
 }
 
+/// The description of a command, labelled branch by branch when the
+/// branches of a conditional document it differently.
+fn format_branches(cmd: &Command) -> String {
+    if cmd.branches.is_empty() {
+        return format_description(&cmd.description);
+    }
+
+    cmd.branches
+        .iter()
+        .map(|branch| match branch.conditions.as_slice() {
+            [] => format_description(&branch.description),
+            conditions => format!(
+                "{} *(when {})*",
+                format_description(&branch.description),
+                format_alternatives(conditions)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+fn format_conditions(conditions: &[Vec<Condition>]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!("<br>*only when {}*", format_alternatives(conditions))
+    }
+}
+
+fn format_alternatives(conditions: &[Vec<Condition>]) -> String {
+    conditions
+        .iter()
+        .map(|c| format!("`{}`", conjunction(c)))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+fn format_variables(variables: &[Variable]) -> String {
+    if variables.is_empty() {
+        String::new()
+    } else {
+        let assignments = variables
+            .iter()
+            .map(|v| format!("`{}`", v))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("<br>*sets {}*", assignments)
+    }
+}
+
+fn format_params(cmd: &Command) -> String {
+    let params: Vec<String> = cmd
+        .params()
+        .into_iter()
+        .map(|(name, explanation)| match explanation {
+            Some(explanation) => format!("`{}` ({})", name, explanation),
+            None => format!("`{}`", name),
+        })
+        .collect();
+
+    if params.is_empty() {
+        "-".to_string()
+    } else {
+        params.join(", ")
+    }
+}
+
+fn format_tag(value: Option<&TagValue>) -> String {
+    match value {
+        Some(TagValue::Text(text)) => format_description(text),
+        Some(TagValue::List(items)) => format_list(items),
+        None => "-".to_string(),
+    }
+}
+
 fn format_list(items: &[String]) -> String {
     if items.is_empty() {
         "-".to_string()
@@ -151,3 +479,262 @@ fn format_list(items: &[String]) -> String {
             .join(", ")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{Tags, parse, parse_with};
+
+    #[test]
+    fn notes_the_conditions_of_each_command() {
+        let doc = parse(
+            r#"
+                ifdef CI
+                ## @description Upload the coverage
+                coverage:
+                endif
+
+                ifeq ($(OS),Windows_NT)
+                ## @description Deploy from Windows
+                deploy:
+                else
+                ## @description Deploy from Unix
+                deploy:
+                endif
+            "#,
+        );
+
+        let md = generate(&doc);
+
+        assert!(md.contains(
+            "| <a id=\"cmd-coverage\"></a>`make coverage` | Upload the coverage<br>*only when `CI is defined`* |"
+        ));
+        assert!(md.contains(concat!(
+            "| <a id=\"cmd-deploy\"></a>`make deploy` | ",
+            "Deploy from Windows *(when `OS=Windows_NT`)*<br>",
+            "Deploy from Unix *(when `OS!=Windows_NT`)* |"
+        )));
+    }
+
+    #[test]
+    fn documents_pattern_rules_as_a_table_and_hexagons() {
+        let doc = parse(
+            r#"
+                ## @description Render a PDF
+                ## @env PANDOC
+                %.pdf: %.md
+
+                ## @description Compile the objects
+                $(OBJS) main.o: %.o: %.c
+            "#,
+        );
+
+        let md = generate(&doc);
+
+        assert!(md.contains(concat!(
+            "## Pattern Rules\n",
+            "| Pattern | Prerequisites | Description | Example | Required Variables |\n",
+            "| :--- | :--- | :--- | :--- | :--- |\n",
+            "| `%.pdf` | `%.md` | Render a PDF | `make example.pdf` from `example.md` | `PANDOC` |\n",
+            "| `%.o` (for `$(OBJS) main.o`) | `%.c` | Compile the objects | `make main.o` from `main.c` | - |\n",
+        )));
+        assert!(md.contains("        pattern_0{{\"%.pdf\"}}\n        pattern_1{{\"%.o\"}}\n"));
+        assert!(md.contains("    class pattern_0 patternRule\n    class pattern_1 patternRule\n"));
+    }
+
+    #[test]
+    fn used_by_mirrors_the_dependencies_column() {
+        let doc = parse(
+            r#"
+                ## @description Install
+                install:
+
+                ## @description Bundle the assets
+                assets:
+
+                ## @description Build
+                ## @depends install
+                build: assets
+            "#,
+        );
+
+        let md = generate(&doc);
+
+        assert!(md.contains(
+            "| <a id=\"cmd-install\"></a>`make install` | Install | - | [`build`](#cmd-build) | - |"
+        ));
+        assert!(md.contains(
+            "| <a id=\"cmd-assets\"></a>`make assets` | Bundle the assets | - | - | - |"
+        ));
+    }
+
+    #[test]
+    fn focused_graphs_are_pieces_of_the_workflow_graph() {
+        let doc = parse(
+            r#"
+                ## @category Build
+                ## @description Build
+                ## @depends docs.pdf
+                build: gen.c
+
+                ## @category Release
+                ## @description Deploy
+                ## @depends build
+                deploy:
+
+                ## @description Render a PDF
+                %.pdf: %.md
+            "#,
+        );
+        let config = Config {
+            focus_depth: Some(1),
+            ..Config::default()
+        };
+
+        let md = generate_with(&doc, &config);
+
+        assert!(md.contains(concat!(
+            "<details>\n",
+            "<summary>Dependency graph of <code>make build</code></summary>\n",
+            "\n",
+            "```mermaid\n",
+            "flowchart LR\n",
+            "    subgraph cat_Build[\"Build\"]\n",
+            "        cmd_build(\"build\")\n",
+            "    end\n",
+            "    style cat_Build fill:transparent,stroke-dasharray: 5 5\n",
+            "    classDef cat0 fill:#E1F5FE,stroke:#01579B,stroke-width:2px,color:#000;\n",
+            "    class cmd_build cat0\n",
+            "    subgraph cat_Release[\"Release\"]\n",
+            "        cmd_deploy(\"deploy\")\n",
+            "    end\n",
+            "    style cat_Release fill:transparent,stroke-dasharray: 5 5\n",
+            "    classDef cat1 fill:#E8F5E9,stroke:#1B5E20,stroke-width:2px,color:#000;\n",
+            "    class cmd_deploy cat1\n",
+            "    subgraph pattern_rules[\"Pattern Rules\"]\n",
+            "        pattern_0{{\"%.pdf\"}}\n",
+            "    end\n",
+            "    style pattern_rules fill:transparent,stroke-dasharray: 5 5\n",
+            "    classDef patternRule fill:#FAFAFA,stroke:#616161,stroke-width:2px,color:#000;\n",
+            "    class pattern_0 patternRule\n",
+            "\n",
+            "    cmd_build --> pattern_0\n",
+            "    cmd_deploy --> cmd_build\n",
+            "    style cmd_build stroke-width:4px,font-weight:bold\n",
+            "```\n",
+            "\n",
+            "</details>\n",
+        )));
+        let details = &md[md.find("## Section Details").unwrap()..];
+        assert!(!details.contains("gen.c"));
+    }
+
+    #[test]
+    fn transitive_reduction_lightens_the_workflow_graph() {
+        let doc = parse(
+            r#"
+                ## @description Build
+                build:
+
+                ## @description Test
+                ## @depends build
+                test:
+
+                ## @description Deploy
+                ## @depends build, test
+                deploy:
+            "#,
+        );
+        let config = Config {
+            transitive_reduction: true,
+            ..Config::default()
+        };
+
+        let md = generate_with(&doc, &config);
+
+        assert!(md.contains("    cmd_test --> cmd_build\n    cmd_deploy --> cmd_test\n```\n"));
+        assert!(!md.contains("cmd_deploy --> cmd_build"));
+        assert!(
+            md.contains("| <a id=\"cmd-deploy\"></a>`make deploy` | Deploy | `build`, `test` |")
+        );
+    }
+
+    #[test]
+    fn lists_the_parameters_in_their_own_column() {
+        let doc = parse(
+            r#"
+                ## @description Seed
+                ## @param VERSION the version to seed, SEED
+                seed:
+
+                ## @description Install
+                install:
+            "#,
+        );
+
+        let md = generate(&doc);
+
+        assert!(md.contains(concat!(
+            "| Command | Description | Dependencies | Used by | Required Variables | Parameters |\n",
+            "| :--- | :--- | :--- | :--- | :--- | :--- |\n",
+            "| <a id=\"cmd-seed\"></a>`make seed` | Seed | - | - | - | `VERSION` (the version to seed), `SEED` |\n",
+            "| <a id=\"cmd-install\"></a>`make install` | Install | - | - | - | - |\n",
+        )));
+    }
+
+    #[test]
+    fn notes_the_target_specific_variables() {
+        let doc = parse(
+            r#"
+                deploy: ENV = prod
+                deploy: export REGION := eu-west-1
+
+                ## @description Deploy
+                deploy:
+            "#,
+        );
+
+        let md = generate(&doc);
+
+        assert!(md.contains(
+            "| <a id=\"cmd-deploy\"></a>`make deploy` | Deploy<br>*sets `ENV=prod`, `REGION:=eu-west-1`* |"
+        ));
+    }
+
+    #[test]
+    fn adds_a_column_per_custom_tag_in_declaration_order() {
+        let config = Config::from_toml(
+            r#"
+                [[tags]]
+                name = "owner"
+                title = "Owner"
+
+                [[tags]]
+                name = "links"
+                list = true
+            "#,
+        )
+        .unwrap();
+        let doc = parse_with(
+            r#"
+                ## @description Deploy
+                ## @links runbook, dashboard
+                ## @owner ops
+                deploy:
+
+                ## @description Lint
+                lint:
+            "#,
+            &Tags::new(&config),
+        );
+
+        let md = generate_with(&doc, &config);
+
+        assert!(md.contains(concat!(
+            "| Command | Description | Dependencies | Used by | Required Variables | Owner | links |\n",
+            "| :--- | :--- | :--- | :--- | :--- | :--- | :--- |\n",
+            "| <a id=\"cmd-deploy\"></a>`make deploy` | Deploy | - | - | - | ops | `runbook`, `dashboard` |\n",
+            "| <a id=\"cmd-lint\"></a>`make lint` | Lint | - | - | - | - | - |\n",
+        )));
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use crate::model::MakefileDoc;
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+    Unvisited,
+    OnStack,
+    Done,
+}
+
+/// Directed graph of the Makefile targets, with an edge from each documented
+/// command to everything it depends on (`@depends` and real prerequisites).
+#[derive(Debug, Clone)]
+pub struct DependencyGraph<'a> {
+    names: Vec<&'a str>,
+    index: HashMap<&'a str, usize>,
+    edges: Vec<Vec<usize>>,
+    reverse_edges: Vec<Vec<usize>>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    pub fn build(doc: &'a MakefileDoc) -> Self {
+        let mut graph = Self {
+            names: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+            reverse_edges: Vec::new(),
+        };
+
+        for cmd in doc.commands() {
+            graph.node(&cmd.name);
+        }
+
+        for cmd in doc.commands() {
+            let from = graph.node(&cmd.name);
+            for dep in cmd.dependencies.iter().chain(&cmd.prerequisites) {
+                let to = graph.node(dep);
+                if !graph.edges[from].contains(&to) {
+                    graph.edges[from].push(to);
+                    graph.reverse_edges[to].push(from);
+                }
+            }
+        }
+
+        graph
+    }
+
+    fn node(&mut self, name: &'a str) -> usize {
+        if let Some(&idx) = self.index.get(name) {
+            return idx;
+        }
+
+        let idx = self.names.len();
+        self.names.push(name);
+        self.index.insert(name, idx);
+        self.edges.push(Vec::new());
+        self.reverse_edges.push(Vec::new());
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, idx: usize) -> &'a str {
+        self.names[idx]
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn dependencies(&self, idx: usize) -> &[usize] {
+        &self.edges[idx]
+    }
+
+    pub fn dependents(&self, idx: usize) -> &[usize] {
+        &self.reverse_edges[idx]
+    }
+
+    /// Every target reachable from `idx`, in declaration order of the edges.
+    pub fn transitive_dependencies(&self, idx: usize) -> Vec<usize> {
+        let mut order = self.post_order(&[idx]);
+        order.retain(|&n| n != idx);
+        order
+    }
+
+    /// The targets make runs for `idx`, dependencies first and `idx` last.
+    pub fn run_order(&self, idx: usize) -> Vec<usize> {
+        self.post_order(&[idx])
+    }
+
+    /// A topological order of the whole graph (dependencies first), or `None`
+    /// when the graph has a cycle.
+    pub fn topological_order(&self) -> Option<Vec<usize>> {
+        if !self.cycles().is_empty() {
+            return None;
+        }
+
+        let all: Vec<usize> = (0..self.len()).collect();
+        Some(self.post_order(&all))
+    }
+
+    fn post_order(&self, starts: &[usize]) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        let mut order = Vec::new();
+
+        for &start in starts {
+            self.visit_post_order(start, &mut visited, &mut order);
+        }
+
+        order
+    }
+
+    fn visit_post_order(&self, node: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+        if visited[node] {
+            return;
+        }
+        visited[node] = true;
+
+        for &next in &self.edges[node] {
+            self.visit_post_order(next, visited, order);
+        }
+
+        order.push(node);
+    }
+
+    /// At least one cycle through each strongly connected component (one per
+    /// back edge of a depth-first walk, not every elementary cycle), each one
+    /// listed once, starting from its earliest declared target.
+    pub fn cycles(&self) -> Vec<Vec<&'a str>> {
+        let mut state = vec![VisitState::Unvisited; self.len()];
+        let mut stack = Vec::new();
+        let mut cycles = Vec::new();
+
+        for node in 0..self.len() {
+            if state[node] == VisitState::Unvisited {
+                self.find_cycles(node, &mut state, &mut stack, &mut cycles);
+            }
+        }
+
+        cycles
+            .into_iter()
+            .map(|cycle: Vec<usize>| cycle.into_iter().map(|idx| self.names[idx]).collect())
+            .collect()
+    }
+
+    fn find_cycles(
+        &self,
+        node: usize,
+        state: &mut [VisitState],
+        stack: &mut Vec<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        state[node] = VisitState::OnStack;
+        stack.push(node);
+
+        for &next in &self.edges[node] {
+            match state[next] {
+                VisitState::Unvisited => self.find_cycles(next, state, stack, cycles),
+                VisitState::OnStack => {
+                    let start = stack.iter().position(|&n| n == next).unwrap_or(0);
+                    let mut cycle = stack[start..].to_vec();
+                    let min = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+                    cycle.rotate_left(min);
+
+                    if !cycles.contains(&cycle) {
+                        cycles.push(cycle);
+                    }
+                }
+                VisitState::Done => {}
+            }
+        }
+
+        stack.pop();
+        state[node] = VisitState::Done;
+    }
+}
+
+/// Drops every edge `a -> b` when `b` can still be reached from `a` through
+/// the edges left so far, keeping the order of the edges that are kept.
+/// Checking against the edges left rather than all of them keeps every node
+/// reachable on graphs with cycles, where two edges can each be redundant
+/// only because of the other.
+pub fn transitive_reduction<T: PartialEq + Clone>(edges: &[(T, T)]) -> Vec<(T, T)> {
+    let mut kept = vec![true; edges.len()];
+
+    for (i, (from, to)) in edges.iter().enumerate() {
+        kept[i] = false;
+        kept[i] = !reachable(edges, &kept, from, to);
+    }
+
+    edges
+        .iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(edge, _)| edge.clone())
+        .collect()
+}
+
+fn reachable<T: PartialEq>(edges: &[(T, T)], kept: &[bool], from: &T, to: &T) -> bool {
+    let mut visited: Vec<&T> = vec![from];
+    let mut stack: Vec<&T> = vec![from];
+
+    while let Some(node) = stack.pop() {
+        for (i, (a, b)) in edges.iter().enumerate() {
+            if !kept[i] || a != node || visited.contains(&b) {
+                continue;
+            }
+            if b == to {
+                return true;
+            }
+            visited.push(b);
+            stack.push(b);
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn builds_edges_from_depends_and_prerequisites() {
+        let content = r#"
+                ## @description Build
+                ## @depends install
+                build: assets
+
+                ## @description Install
+                install:
+            "#;
+
+        let doc = parse(content);
+        let graph = DependencyGraph::build(&doc);
+
+        let build = graph.index_of("build").unwrap();
+        let deps: Vec<&str> = graph
+            .dependencies(build)
+            .iter()
+            .map(|&idx| graph.name(idx))
+            .collect();
+
+        assert_eq!(deps, vec!["install", "assets"]);
+
+        let install = graph.index_of("install").unwrap();
+        assert_eq!(graph.dependents(install), &[build]);
+        assert_eq!(graph.len(), 3);
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn orders_transitive_dependencies_first() {
+        let content = r#"
+                ## @description Deploy
+                ## @depends build, migrate
+                deploy:
+
+                ## @description Build
+                build: install
+
+                ## @description Migrate
+                ## @depends up, install
+                migrate:
+            "#;
+
+        let doc = parse(content);
+        let graph = DependencyGraph::build(&doc);
+        let names =
+            |order: Vec<usize>| order.into_iter().map(|i| graph.name(i)).collect::<Vec<_>>();
+
+        let deploy = graph.index_of("deploy").unwrap();
+        assert_eq!(
+            names(graph.run_order(deploy)),
+            vec!["install", "build", "up", "migrate", "deploy"]
+        );
+        assert_eq!(
+            names(graph.transitive_dependencies(deploy)),
+            vec!["install", "build", "up", "migrate"]
+        );
+
+        let order = names(graph.topological_order().unwrap());
+        assert_eq!(order.len(), graph.len());
+        assert!(
+            order.iter().position(|n| *n == "install") < order.iter().position(|n| *n == "build")
+        );
+    }
+
+    #[test]
+    fn transitive_reduction_drops_redundant_edges() {
+        let edges = [
+            ("deploy", "build"),
+            ("deploy", "test"),
+            ("test", "build"),
+            ("build", "install"),
+            ("deploy", "install"),
+        ];
+
+        assert_eq!(
+            transitive_reduction(&edges),
+            vec![("deploy", "test"), ("test", "build"), ("build", "install")]
+        );
+    }
+
+    #[test]
+    fn transitive_reduction_keeps_reachability_through_cycles() {
+        let edges = [("a", "b"), ("b", "a"), ("a", "c"), ("b", "c")];
+
+        assert_eq!(
+            transitive_reduction(&edges),
+            vec![("a", "b"), ("b", "a"), ("b", "c")]
+        );
+    }
+
+    #[test]
+    fn reports_each_cycle_once_with_its_full_path() {
+        let content = r#"
+                ## @description A
+                ## @depends b
+                a:
+
+                ## @description B
+                b: c
+
+                ## @description C
+                ## @depends a
+                c: c
+            "#;
+
+        let doc = parse(content);
+        let cycles = DependencyGraph::build(&doc).cycles();
+
+        assert_eq!(cycles, vec![vec!["a", "b", "c"], vec!["c"]]);
+        assert_eq!(DependencyGraph::build(&doc).topological_order(), None);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{config::Config, model::MakefileDoc, render::Renderer};
+
+pub const HELP_FILE_NAME: &str = "help.mk";
+pub const BEGIN_MARKER: &str = "# >>> makefile2doc help target >>>";
+pub const END_MARKER: &str = "# <<< makefile2doc help target <<<";
+
+/// A `help` target written in plain make, for machines where makefile2doc is
+/// not installed. The output is wrapped in the begin and end markers.
+pub struct HelpTargetRenderer;
+
+impl Renderer for HelpTargetRenderer {
+    fn render(&self, doc: &MakefileDoc, _config: &Config) -> String {
+        let name_width = doc
+            .commands()
+            .map(|cmd| cmd.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let row = format!("\t@printf '  %-{}s  %s\\n'", name_width);
+
+        let mut mk = String::new();
+        mk.push_str(BEGIN_MARKER);
+        mk.push('\n');
+        mk.push_str("# Generated by makefile2doc, do not edit by hand.\n");
+        mk.push_str(".PHONY: help\n");
+        mk.push_str("help:\n");
+        mk.push_str("\t@printf '%s\\n' 'Usage: make <target>'\n");
+
+        for cat in &doc.categories {
+            mk.push_str(&format!("\t@printf '\\n%s\\n' {}\n", quote(&cat.name)));
+
+            for cmd in &cat.commands {
+                let mut lines = cmd.description.split("\\n").map(str::trim);
+                mk.push_str(&format!(
+                    "{} {} {}\n",
+                    row,
+                    quote(&cmd.name),
+                    quote(lines.next().unwrap_or_default())
+                ));
+                for line in lines {
+                    mk.push_str(&format!("{} '' {}\n", row, quote(line)));
+                }
+            }
+        }
+
+        mk.push_str(END_MARKER);
+        mk.push('\n');
+        mk
+    }
+}
+
+/// Quotes a string for the shell running the recipe, escaping `$` for make.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''").replace('$', "$$"))
+}
+
+fn find_region(content: &str) -> Result<Option<(usize, usize)>, &'static str> {
+    let Some(start) = content.find(BEGIN_MARKER) else {
+        return match content.contains(END_MARKER) {
+            true => Err("The help target region has an end marker but no begin marker"),
+            false => Ok(None),
+        };
+    };
+    let Some(end) = content[start..].find(END_MARKER) else {
+        return Err("The help target region has a begin marker but no end marker");
+    };
+
+    let end = start + end + END_MARKER.len();
+    let end = match content[end..].starts_with('\n') {
+        true => end + 1,
+        false => end,
+    };
+    Ok(Some((start, end)))
+}
+
+/// The Makefile without its generated region, so the `help` target is never
+/// documented by the next run.
+pub fn strip_region(content: &str) -> Result<String, &'static str> {
+    Ok(match find_region(content)? {
+        Some((start, end)) => format!("{}{}", &content[..start], &content[end..]),
+        None => content.to_string(),
+    })
+}
+
+/// Replaces the generated region of the Makefile with `region`, or appends it
+/// at the end when the Makefile has none yet. A marker without its pair is an
+/// error, rather than a reason to append a second region.
+pub fn update_region(content: &str, region: &str) -> Result<String, &'static str> {
+    Ok(match find_region(content)? {
+        Some((start, end)) => format!("{}{}{}", &content[..start], region, &content[end..]),
+        None if content.is_empty() => region.to_string(),
+        None => {
+            let separator = if content.ends_with("\n\n") {
+                ""
+            } else if content.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            format!("{}{}{}", content, separator, region)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn generates_printf_lines_for_every_command() {
+        let doc = parse(
+            r#"
+                ## @category Database
+                ## @description Run the migrations\nNeeds $DATABASE_URL
+                migrate:
+
+                ## @description Don't run in prod
+                seed:
+            "#,
+        );
+
+        let mk = HelpTargetRenderer.render(&doc, &Config::default());
+
+        assert_eq!(
+            mk,
+            format!(
+                concat!(
+                    "{}\n",
+                    "# Generated by makefile2doc, do not edit by hand.\n",
+                    ".PHONY: help\n",
+                    "help:\n",
+                    "\t@printf '%s\\n' 'Usage: make <target>'\n",
+                    "\t@printf '\\n%s\\n' 'Database'\n",
+                    "\t@printf '  %-7s  %s\\n' 'migrate' 'Run the migrations'\n",
+                    "\t@printf '  %-7s  %s\\n' '' 'Needs $$DATABASE_URL'\n",
+                    "\t@printf '  %-7s  %s\\n' 'seed' 'Don'\\''t run in prod'\n",
+                    "{}\n",
+                ),
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+    }
+
+    #[test]
+    fn updating_the_region_is_idempotent() {
+        let region = format!("{}\nhelp:\n{}\n", BEGIN_MARKER, END_MARKER);
+        let makefile = "build:\n\tcargo build\n";
+
+        let once = update_region(makefile, &region).unwrap();
+        assert_eq!(once, format!("build:\n\tcargo build\n\n{}", region));
+        assert_eq!(update_region(&once, &region).unwrap(), once);
+        assert_eq!(strip_region(&once).unwrap(), "build:\n\tcargo build\n\n");
+
+        let newer = format!("{}\nhelp: ; @true\n{}\n", BEGIN_MARKER, END_MARKER);
+        let updated = update_region(&format!("{}\ntest:\n", once), &newer).unwrap();
+        assert_eq!(
+            updated,
+            format!("build:\n\tcargo build\n\n{}\ntest:\n", newer)
+        );
+    }
+
+    #[test]
+    fn a_half_open_region_is_an_error() {
+        let region = format!("{}\nhelp:\n{}\n", BEGIN_MARKER, END_MARKER);
+
+        for makefile in [
+            format!("build:\n{}\nhelp:\n", BEGIN_MARKER),
+            format!("build:\nhelp:\n{}\n", END_MARKER),
+        ] {
+            assert!(update_region(&makefile, &region).is_err());
+            assert!(strip_region(&makefile).is_err());
+        }
+    }
+}
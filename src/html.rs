@@ -0,0 +1,434 @@
+use crate::{
+    anchor::AnchorManager,
+    config::Config,
+    generator::{command_anchor, stem_example, workflow_flowchart},
+    lexer::{Condition, conjunction},
+    model::{Command, MakefileDoc, PatternRule, TagValue, Variable},
+    render::Renderer,
+};
+
+/// Mermaid is inlined so that the page draws its graph offline.
+const MERMAID: &str = include_str!("../documentation/mermaid.min.js");
+
+const STYLE: &str = r#"
+:root { color-scheme: light dark; --accent: #01579B; --muted: #6b7280; --border: #d0d7de; }
+* { box-sizing: border-box; }
+body { margin: 0; display: flex; font-family: system-ui, -apple-system, "Segoe UI", sans-serif; line-height: 1.5; }
+nav { position: sticky; top: 0; align-self: flex-start; width: 16rem; height: 100vh; overflow-y: auto; padding: 1.5rem 1rem; border-right: 1px solid var(--border); }
+nav ul { list-style: none; margin: 0; padding: 0; }
+nav li { margin: 0.25rem 0; }
+nav a { color: inherit; text-decoration: none; }
+nav a:hover { color: var(--accent); }
+main { flex: 1; min-width: 0; padding: 1.5rem 2rem; }
+.credit { color: var(--muted); font-style: italic; }
+#filter { width: 100%; max-width: 32rem; padding: 0.5rem 0.75rem; font-size: 1rem; border: 1px solid var(--border); border-radius: 6px; }
+table { width: 100%; border-collapse: collapse; margin: 0.5rem 0 1.5rem; }
+th, td { padding: 0.4rem 0.6rem; border: 1px solid var(--border); text-align: left; vertical-align: top; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9em; }
+td.command { white-space: nowrap; }
+button.copy { margin-left: 0.4rem; padding: 0 0.4rem; font-size: 0.75rem; cursor: pointer; border: 1px solid var(--border); border-radius: 4px; background: transparent; color: inherit; }
+.note { color: var(--muted); font-style: italic; }
+pre.mermaid { overflow-x: auto; }
+@media (max-width: 50rem) { body { display: block; } nav { position: static; width: auto; height: auto; border-right: none; border-bottom: 1px solid var(--border); } }
+"#;
+
+const SCRIPT: &str = r##"
+const filter = document.getElementById("filter");
+filter.addEventListener("input", () => {
+  const query = filter.value.trim().toLowerCase();
+  let matches = 0;
+  document.querySelectorAll("section.category").forEach((section) => {
+    let shown = 0;
+    section.querySelectorAll("tr.command").forEach((row) => {
+      row.hidden = !row.textContent.toLowerCase().includes(query);
+      if (!row.hidden) shown++;
+    });
+    section.hidden = shown === 0;
+    document.querySelector(`nav a[href="#${section.id}"]`).parentElement.hidden = shown === 0;
+    matches += shown;
+  });
+  document.getElementById("no-match").hidden = matches > 0;
+});
+
+document.querySelectorAll("button.copy").forEach((button) => {
+  button.addEventListener("click", async () => {
+    await navigator.clipboard.writeText(button.dataset.copy);
+    button.textContent = "Copied";
+    setTimeout(() => (button.textContent = "Copy"), 1500);
+  });
+});
+"##;
+
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        let anchors = AnchorManager::build(doc);
+        let mut html = String::new();
+
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        html.push_str("<meta charset=\"utf-8\">\n");
+        html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+        html.push_str("<title>Makefile Documentation</title>\n");
+        html.push_str(&format!("<style>{}</style>\n", STYLE));
+        html.push_str("</head>\n<body>\n");
+
+        html.push_str(&generate_navigation(doc, &anchors));
+
+        html.push_str("<main>\n<h1>Makefile Documentation</h1>\n");
+        html.push_str(concat!(
+            "<p class=\"credit\">Auto-generated by <a href=\"",
+            env!("CARGO_PKG_REPOSITORY"),
+            "\">makefile2doc</a></p>\n"
+        ));
+        html.push_str(
+            "<input type=\"search\" id=\"filter\" placeholder=\"Filter commands...\" autofocus>\n",
+        );
+        html.push_str(
+            "<p id=\"no-match\" class=\"note\" hidden>No command matches your search.</p>\n",
+        );
+
+        html.push_str(&generate_categories(doc, &anchors, config));
+
+        html.push_str("<section id=\"workflow-graph\">\n<h2>Workflow Graph</h2>\n");
+        html.push_str(&format!(
+            "<pre class=\"mermaid\">\n{}</pre>\n</section>\n",
+            escape(&workflow_flowchart(doc, config))
+        ));
+
+        if !doc.patterns.is_empty() {
+            html.push_str(&generate_pattern_rules(doc));
+        }
+
+        html.push_str("</main>\n");
+        html.push_str(&format!("<script>{}</script>\n", SCRIPT));
+        html.push_str(&format!("<script>\n{}\n</script>\n", MERMAID));
+        html.push_str("<script>mermaid.initialize({ startOnLoad: true });</script>\n");
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn generate_navigation(doc: &MakefileDoc, anchors: &AnchorManager) -> String {
+    let mut nav = String::new();
+
+    nav.push_str("<nav>\n<h2>Categories</h2>\n<ul>\n");
+    for (cat_idx, cat) in doc.categories.iter().enumerate() {
+        nav.push_str(&format!(
+            "<li><a href=\"#{}\">{}</a></li>\n",
+            anchors.category_id(cat_idx),
+            escape(&cat.name)
+        ));
+    }
+    nav.push_str("</ul>\n<ul>\n<li><a href=\"#workflow-graph\">Workflow Graph</a></li>\n");
+    if !doc.patterns.is_empty() {
+        nav.push_str("<li><a href=\"#pattern-rules\">Pattern Rules</a></li>\n");
+    }
+    nav.push_str("</ul>\n</nav>\n");
+    nav
+}
+
+fn generate_categories(doc: &MakefileDoc, anchors: &AnchorManager, config: &Config) -> String {
+    let has_params = doc.commands().any(|cmd| !cmd.params().is_empty());
+    let mut section = String::new();
+
+    for (cat_idx, cat) in doc.categories.iter().enumerate() {
+        section.push_str(&format!(
+            "<section class=\"category\" id=\"{}\">\n<h2>{}</h2>\n",
+            anchors.category_id(cat_idx),
+            escape(&cat.name)
+        ));
+        section.push_str("<table>\n<thead>\n<tr><th>Command</th><th>Description</th><th>Dependencies</th><th>Used by</th><th>Required Variables</th>");
+        if has_params {
+            section.push_str("<th>Parameters</th>");
+        }
+        for tag in &config.tags {
+            section.push_str(&format!("<th>{}</th>", escape(tag.title())));
+        }
+        section.push_str("</tr>\n</thead>\n<tbody>\n");
+
+        for (cmd_idx, cmd) in cat.commands.iter().enumerate() {
+            let snippet = format!("make {}", cmd.name);
+            section.push_str(&format!(
+                "<tr class=\"command\" id=\"{}\"><td class=\"command\"><code>{}</code><button class=\"copy\" data-copy=\"{}\" title=\"Copy to clipboard\">Copy</button></td>",
+                anchors.command_id(cat_idx, cmd_idx),
+                escape(&snippet),
+                escape(&snippet)
+            ));
+            section.push_str(&format!(
+                "<td>{}{}{}</td>",
+                format_branches(cmd),
+                format_conditions(&cmd.conditions),
+                format_variables(&cmd.variables)
+            ));
+            section.push_str(&format!(
+                "<td>{}</td>",
+                format_links(doc, anchors, cmd.dependencies.iter().map(String::as_str))
+            ));
+
+            let used_by = doc.dependents(&cmd.name).map(|c| c.name.as_str());
+            section.push_str(&format!("<td>{}</td>", format_links(doc, anchors, used_by)));
+            section.push_str(&format!("<td>{}</td>", format_list(&cmd.env)));
+            if has_params {
+                section.push_str(&format!("<td>{}</td>", format_params(cmd)));
+            }
+
+            for tag in &config.tags {
+                let value = match cmd.tags.get(&tag.name) {
+                    Some(TagValue::Text(text)) => format_description(text),
+                    Some(TagValue::List(items)) => format_list(items),
+                    None => "-".to_string(),
+                };
+                section.push_str(&format!("<td>{}</td>", value));
+            }
+            section.push_str("</tr>\n");
+        }
+
+        section.push_str("</tbody>\n</table>\n</section>\n");
+    }
+
+    section
+}
+
+fn generate_pattern_rules(doc: &MakefileDoc) -> String {
+    let mut section = String::new();
+
+    section.push_str("<section id=\"pattern-rules\">\n<h2>Pattern Rules</h2>\n");
+    section.push_str("<table>\n<thead>\n<tr><th>Pattern</th><th>Prerequisites</th><th>Description</th><th>Example</th><th>Required Variables</th></tr>\n</thead>\n<tbody>\n");
+
+    for pattern in &doc.patterns {
+        let name = match &pattern.targets {
+            Some(targets) => format!(
+                "<code>{}</code> (for <code>{}</code>)",
+                escape(&pattern.pattern),
+                escape(targets)
+            ),
+            None => format!("<code>{}</code>", escape(&pattern.pattern)),
+        };
+        section.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            name,
+            format_list(&pattern.prerequisites),
+            format_description(&pattern.description),
+            format_example(pattern),
+            format_list(&pattern.env)
+        ));
+    }
+
+    section.push_str("</tbody>\n</table>\n</section>\n");
+    section
+}
+
+fn format_example(pattern: &PatternRule) -> String {
+    let (target, sources) = stem_example(pattern);
+    let sources: Vec<String> = sources
+        .iter()
+        .map(|s| format!("<code>{}</code>", escape(s)))
+        .collect();
+
+    if sources.is_empty() {
+        format!("<code>make {}</code>", escape(&target))
+    } else {
+        format!(
+            "<code>make {}</code> from {}",
+            escape(&target),
+            sources.join(", ")
+        )
+    }
+}
+
+fn format_links<'a>(
+    doc: &MakefileDoc,
+    anchors: &AnchorManager,
+    names: impl Iterator<Item = &'a str>,
+) -> String {
+    let links: Vec<String> = names
+        .map(|name| match command_anchor(doc, anchors, name) {
+            Some(anchor) => format!("<a href=\"#{}\"><code>{}</code></a>", anchor, escape(name)),
+            None => format!("<code>{}</code>", escape(name)),
+        })
+        .collect();
+
+    if links.is_empty() {
+        "-".to_string()
+    } else {
+        links.join(", ")
+    }
+}
+
+fn format_description(desc: &str) -> String {
+    if desc.is_empty() {
+        "-".to_string()
+    } else {
+        escape(desc).replace("\\n", "<br>")
+    }
+}
+
+fn format_branches(cmd: &Command) -> String {
+    if cmd.branches.is_empty() {
+        return format_description(&cmd.description);
+    }
+
+    cmd.branches
+        .iter()
+        .map(|branch| match branch.conditions.as_slice() {
+            [] => format_description(&branch.description),
+            conditions => format!(
+                "{} <span class=\"note\">(when {})</span>",
+                format_description(&branch.description),
+                format_alternatives(conditions)
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+fn format_conditions(conditions: &[Vec<Condition>]) -> String {
+    if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<br><span class=\"note\">only when {}</span>",
+            format_alternatives(conditions)
+        )
+    }
+}
+
+fn format_alternatives(conditions: &[Vec<Condition>]) -> String {
+    conditions
+        .iter()
+        .map(|c| format!("<code>{}</code>", escape(&conjunction(c))))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+fn format_variables(variables: &[Variable]) -> String {
+    if variables.is_empty() {
+        String::new()
+    } else {
+        let assignments = variables
+            .iter()
+            .map(|v| format!("<code>{}</code>", escape(&v.to_string())))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("<br><span class=\"note\">sets {}</span>", assignments)
+    }
+}
+
+fn format_params(cmd: &Command) -> String {
+    let params: Vec<String> = cmd
+        .params()
+        .into_iter()
+        .map(|(name, explanation)| match explanation {
+            Some(explanation) => format!("<code>{}</code> ({})", escape(name), escape(explanation)),
+            None => format!("<code>{}</code>", escape(name)),
+        })
+        .collect();
+
+    if params.is_empty() {
+        "-".to_string()
+    } else {
+        params.join(", ")
+    }
+}
+
+fn format_list(items: &[String]) -> String {
+    if items.is_empty() {
+        "-".to_string()
+    } else {
+        items
+            .iter()
+            .map(|i| format!("<code>{}</code>", escape(i)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn escape_handles_markup_characters() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn renders_a_standalone_page_with_navigation_search_and_graph() {
+        let doc = parse(
+            r#"
+                ## @category Setup & Tools
+                ## @description Install <everything>
+                install:
+
+                ## @description Build the app
+                ## @depends install
+                build:
+            "#,
+        );
+
+        let html = HtmlRenderer.render(&doc, &Config::default());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<li><a href=\"#cat-setup-tools\">Setup &amp; Tools</a></li>"));
+        assert!(html.contains("<input type=\"search\" id=\"filter\""));
+        assert!(html.contains("<td>Install &lt;everything&gt;</td>"));
+        assert!(html.contains("data-copy=\"make build\""));
+        assert!(html.contains("<a href=\"#cmd-install\"><code>install</code></a>"));
+        assert!(html.contains("<pre class=\"mermaid\">\nflowchart LR\n"));
+        assert!(html.contains("cmd_build --&gt; cmd_install"));
+        assert!(!html.contains("id=\"pattern-rules\""));
+        assert!(!html.contains("<th>Parameters</th>"));
+        assert!(html.contains("globalThis.mermaid"));
+        assert!(!html.contains("https://cdn."));
+    }
+
+    #[test]
+    fn parameters_get_their_own_column() {
+        let doc = parse(
+            r#"
+                ## @description Seed the database
+                ## @param VERSION the <version>, SEED
+                seed:
+
+                ## @description Install
+                install:
+            "#,
+        );
+
+        let html = HtmlRenderer.render(&doc, &Config::default());
+
+        assert!(html.contains("<th>Required Variables</th><th>Parameters</th>"));
+        assert!(html.contains(
+            "<td><code>VERSION</code> (the &lt;version&gt;), <code>SEED</code></td></tr>"
+        ));
+        assert!(html.contains("<td>-</td><td>-</td></tr>"));
+    }
+
+    #[test]
+    fn pattern_rules_show_an_example() {
+        let doc = parse(
+            r#"
+                ## @description Compile the object files
+                $(OBJS) main.o: %.o: %.c
+            "#,
+        );
+
+        let html = HtmlRenderer.render(&doc, &Config::default());
+
+        assert!(html.contains("<th>Description</th><th>Example</th>"));
+        assert!(html.contains("<td><code>make main.o</code> from <code>main.c</code></td>"));
+    }
+}
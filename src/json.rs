@@ -0,0 +1,107 @@
+use schemars::{JsonSchema, schema_for};
+use serde::Serialize;
+
+use crate::{config::Config, model::MakefileDoc, render::Renderer};
+
+/// Bumped whenever a field is renamed, removed or changes meaning. Adding a
+/// field keeps the same version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, JsonSchema)]
+#[schemars(title = "makefile2doc export")]
+struct JsonDocument<'a> {
+    /// Version of the export format, bumped on breaking changes.
+    schema_version: u32,
+    #[serde(flatten)]
+    doc: &'a MakefileDoc,
+}
+
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn render(&self, doc: &MakefileDoc, _config: &Config) -> String {
+        let document = JsonDocument {
+            schema_version: SCHEMA_VERSION,
+            doc,
+        };
+
+        let mut json =
+            serde_json::to_string_pretty(&document).expect("the model is always serializable");
+        json.push('\n');
+        json
+    }
+}
+
+/// The JSON Schema of the `--format json` output, derived from the same types
+/// as the export itself.
+pub fn schema() -> String {
+    let mut json = serde_json::to_string_pretty(&schema_for!(JsonDocument))
+        .expect("the schema is always serializable");
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn exports_the_model_with_its_schema_version() {
+        let doc = parse(
+            r#"
+                ## @category Build
+                ## @description Build the app
+                ## @depends install
+                ## @env PROFILE
+                build: assets
+
+                ## @description Compile a PDF
+                %.pdf: %.md
+            "#,
+        );
+
+        let output = JsonRenderer.render(&doc, &Config::default());
+        let value: Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["categories"][0]["name"], "Build");
+        assert_eq!(
+            value["categories"][0]["commands"][0],
+            json!({
+                "name": "build",
+                "description": "Build the app",
+                "dependencies": ["install"],
+                "prerequisites": ["assets"],
+                "env": ["PROFILE"],
+                "conditions": [],
+                "branches": [],
+                "variables": [],
+                "tags": {},
+            })
+        );
+        assert_eq!(value["patterns"][0]["pattern"], "%.pdf");
+    }
+
+    #[test]
+    fn schema_describes_the_exported_fields() {
+        let schema: Value = serde_json::from_str(&schema()).unwrap();
+
+        assert_eq!(schema["title"], "makefile2doc export");
+        assert_eq!(schema["properties"]["schema_version"]["type"], "integer");
+        assert!(schema["properties"]["categories"].is_object());
+
+        let command = &schema["$defs"]["Command"]["properties"];
+        for field in [
+            "name",
+            "description",
+            "dependencies",
+            "prerequisites",
+            "env",
+            "tags",
+        ] {
+            assert!(command[field].is_object(), "missing '{}'", field);
+        }
+    }
+}
@@ -0,0 +1,425 @@
+#[cfg(feature = "json")]
+use schemars::JsonSchema;
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LineKind {
+    Comment,
+    Rule,
+    Assignment,
+    Conditional,
+    Directive,
+    Recipe,
+    DefineBody,
+    Continuation,
+    Blank,
+    Other,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
+#[cfg_attr(feature = "json", serde(rename_all = "snake_case"))]
+pub enum Condition {
+    Equal(String, String),
+    NotEqual(String, String),
+    Defined(String),
+    NotDefined(String),
+}
+
+impl Condition {
+    fn parse(keyword: &str, args: &str) -> Option<Self> {
+        match keyword {
+            "ifdef" => Some(Self::Defined(args.trim().to_string())),
+            "ifndef" => Some(Self::NotDefined(args.trim().to_string())),
+            "ifeq" | "ifneq" => {
+                let (left, right) = split_comparison(args.trim())?;
+                let (left, right) = (unwrap_variable(left), unwrap_variable(right));
+                if keyword == "ifeq" {
+                    Some(Self::Equal(left, right))
+                } else {
+                    Some(Self::NotEqual(left, right))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn negate(&self) -> Self {
+        match self {
+            Self::Equal(l, r) => Self::NotEqual(l.clone(), r.clone()),
+            Self::NotEqual(l, r) => Self::Equal(l.clone(), r.clone()),
+            Self::Defined(v) => Self::NotDefined(v.clone()),
+            Self::NotDefined(v) => Self::Defined(v.clone()),
+        }
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equal(l, r) => write!(f, "{}={}", l, r),
+            Self::NotEqual(l, r) => write!(f, "{}!={}", l, r),
+            Self::Defined(v) => write!(f, "{} is defined", v),
+            Self::NotDefined(v) => write!(f, "{} is not defined", v),
+        }
+    }
+}
+
+/// Renders conditions that must all hold, e.g.
+/// `OS=Windows_NT and DEBUG is defined`.
+pub fn conjunction(conditions: &[Condition]) -> String {
+    conditions
+        .iter()
+        .map(Condition::to_string)
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+struct ConditionalFrame {
+    previous: Vec<Condition>,
+    current: Vec<Condition>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+    pub kind: LineKind,
+}
+
+/// Classifies Makefile lines while keeping track of the context make itself
+/// would be in (recipes, `define` blocks, conditionals), so that only genuine
+/// rule headers and top-level comments reach the parser.
+pub struct Lexer<'a> {
+    lines: std::vec::IntoIter<(usize, &'a str)>,
+    in_rule: bool,
+    define_depth: usize,
+    conditionals: Vec<ConditionalFrame>,
+    continued: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(lines: Vec<(usize, &'a str)>) -> Self {
+        Self {
+            lines: lines.into_iter(),
+            in_rule: false,
+            define_depth: 0,
+            conditionals: Vec::new(),
+            continued: false,
+        }
+    }
+
+    /// The conditions under which the last returned line is read by make,
+    /// all of which must hold.
+    pub fn conditions(&self) -> Vec<Condition> {
+        self.conditionals
+            .iter()
+            .flat_map(|frame| frame.current.iter().cloned())
+            .collect()
+    }
+
+    fn open_conditional(&mut self, text: &str) {
+        let keyword = first_word(text);
+        let condition = Condition::parse(keyword, &text[keyword.len()..]);
+
+        self.conditionals.push(ConditionalFrame {
+            previous: condition.iter().cloned().collect(),
+            current: condition.into_iter().collect(),
+        });
+    }
+
+    fn switch_conditional(&mut self, text: &str) {
+        let Some(frame) = self.conditionals.last_mut() else {
+            return;
+        };
+
+        let rest = text["else".len()..].trim();
+        let keyword = first_word(rest);
+        let condition = Condition::parse(keyword, &rest[keyword.len()..]);
+
+        frame.current = frame.previous.iter().map(Condition::negate).collect();
+        frame.current.extend(condition.iter().cloned());
+        frame.previous.extend(condition);
+    }
+
+    fn classify(&mut self, raw: &str) -> LineKind {
+        let text = raw.trim();
+
+        if self.continued {
+            self.continued = ends_with_continuation(raw);
+            return LineKind::Continuation;
+        }
+        self.continued = ends_with_continuation(raw);
+
+        if self.define_depth > 0 {
+            match first_word(strip_define_modifiers(text)) {
+                "define" => self.define_depth += 1,
+                "endef" => self.define_depth -= 1,
+                _ => {}
+            }
+            return LineKind::DefineBody;
+        }
+
+        if text.is_empty() {
+            return LineKind::Blank;
+        }
+
+        if self.in_rule && raw.starts_with('\t') {
+            return LineKind::Recipe;
+        }
+
+        if text.starts_with('#') {
+            return LineKind::Comment;
+        }
+
+        match first_word(text) {
+            "ifeq" | "ifneq" | "ifdef" | "ifndef" => {
+                self.open_conditional(text);
+                return LineKind::Conditional;
+            }
+            "else" => {
+                self.switch_conditional(text);
+                return LineKind::Conditional;
+            }
+            "endif" => {
+                self.conditionals.pop();
+                return LineKind::Conditional;
+            }
+            _ => {}
+        }
+
+        self.in_rule = false;
+
+        if first_word(strip_define_modifiers(text)) == "define" {
+            self.define_depth = 1;
+            return LineKind::Directive;
+        }
+
+        if matches!(
+            first_word(text),
+            "include" | "-include" | "sinclude" | "vpath" | "undefine"
+        ) {
+            return LineKind::Directive;
+        }
+
+        match find_separator(text) {
+            Some(Separator::Assignment) => LineKind::Assignment,
+            Some(Separator::Rule) => {
+                self.in_rule = true;
+                LineKind::Rule
+            }
+            None if matches!(first_word(text), "export" | "unexport") => LineKind::Directive,
+            None => LineKind::Other,
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (number, raw) = self.lines.next()?;
+        let kind = self.classify(raw);
+
+        Some(Line {
+            number,
+            text: raw.trim(),
+            kind,
+        })
+    }
+}
+
+enum Separator {
+    Rule,
+    Assignment,
+}
+
+/// Finds whether the first top-level `:` or `=` of a line makes it a rule or
+/// a variable assignment (`:=`, `::=` and `:::=` are assignments).
+fn find_separator(text: &str) -> Option<Separator> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'(' | b'{' => depth += 1,
+            b')' | b'}' => depth = depth.saturating_sub(1),
+            b'=' if depth == 0 => return Some(Separator::Assignment),
+            b':' if depth == 0 => {
+                let mut j = i + 1;
+                while j < bytes.len() && j - i < 3 && bytes[j] == b':' {
+                    j += 1;
+                }
+                if bytes.get(j) == Some(&b'=') {
+                    return Some(Separator::Assignment);
+                }
+                return Some(Separator::Rule);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    None
+}
+
+fn split_comparison(args: &str) -> Option<(&str, &str)> {
+    if let Some(inner) = args.strip_prefix('(').and_then(|a| a.strip_suffix(')')) {
+        let mut depth = 0usize;
+        for (i, c) in inner.char_indices() {
+            match c {
+                '(' | '{' => depth += 1,
+                ')' | '}' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => return Some((&inner[..i], &inner[i + 1..])),
+                _ => {}
+            }
+        }
+        return None;
+    }
+
+    let quoted: Vec<&str> = args
+        .split(['"', '\''])
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match quoted.as_slice() {
+        [left, right] => Some((left, right)),
+        _ => None,
+    }
+}
+
+fn unwrap_variable(arg: &str) -> String {
+    let arg = arg.trim();
+    arg.strip_prefix("$(")
+        .and_then(|a| a.strip_suffix(')'))
+        .or_else(|| arg.strip_prefix("${").and_then(|a| a.strip_suffix('}')))
+        .filter(|name| !name.contains(['$', '(', '{', ' ']))
+        .unwrap_or(arg)
+        .to_string()
+}
+
+fn first_word(text: &str) -> &str {
+    text.split(|c: char| c.is_whitespace() || c == '(')
+        .next()
+        .unwrap_or("")
+}
+
+fn strip_define_modifiers(text: &str) -> &str {
+    let mut rest = text;
+    while let Some(word) = ["override", "export", "private"]
+        .iter()
+        .find(|w| first_word(rest) == **w)
+    {
+        rest = rest[word.len()..].trim_start();
+    }
+    rest
+}
+
+fn ends_with_continuation(raw: &str) -> bool {
+    let trailing = raw
+        .trim_end()
+        .bytes()
+        .rev()
+        .take_while(|b| *b == b'\\')
+        .count();
+    trailing % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<LineKind> {
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l))
+            .collect();
+        Lexer::new(lines).map(|line| line.kind).collect()
+    }
+
+    #[test]
+    fn distinguishes_rules_from_assignments() {
+        assert_eq!(
+            kinds("build: src\nCC := gcc\nX::=y\nY ?= z\nclean::\n$(OUT): $(SRC:.c=.o)"),
+            vec![
+                LineKind::Rule,
+                LineKind::Assignment,
+                LineKind::Assignment,
+                LineKind::Assignment,
+                LineKind::Rule,
+                LineKind::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn recipes_and_define_blocks_are_opaque() {
+        let content = "build:\n\techo foo: bar\n\n\t## @description nope\ndefine TEMPLATE\n## @description nope\nfake:\nendef\nreal:";
+
+        assert_eq!(
+            kinds(content),
+            vec![
+                LineKind::Rule,
+                LineKind::Recipe,
+                LineKind::Blank,
+                LineKind::Recipe,
+                LineKind::Directive,
+                LineKind::DefineBody,
+                LineKind::DefineBody,
+                LineKind::DefineBody,
+                LineKind::Rule,
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_conditionals_and_continuations() {
+        let content = "ifeq ($(OS),Windows_NT)\nFILES = a \\\n  b: c\nelse\nendif\nendif";
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l))
+            .collect();
+        let mut lexer = Lexer::new(lines);
+
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Conditional);
+        assert_eq!(conjunction(&lexer.conditions()), "OS=Windows_NT");
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Assignment);
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Continuation);
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Conditional);
+        assert_eq!(conjunction(&lexer.conditions()), "OS!=Windows_NT");
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Conditional);
+        assert!(lexer.conditions().is_empty());
+        assert_eq!(lexer.next().unwrap().kind, LineKind::Conditional);
+        assert!(lexer.conditions().is_empty());
+    }
+
+    #[test]
+    fn renders_nested_and_chained_conditions() {
+        let content = "ifdef CI\nifneq \"$(ENV)\" \"prod\"\nendif\nelse ifeq ($(ENV),dev)\nendif";
+        let lines = content
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l))
+            .collect();
+        let mut lexer = Lexer::new(lines);
+
+        lexer.next();
+        assert_eq!(conjunction(&lexer.conditions()), "CI is defined");
+        lexer.next();
+        assert_eq!(
+            conjunction(&lexer.conditions()),
+            "CI is defined and ENV!=prod"
+        );
+        lexer.next();
+        lexer.next();
+        assert_eq!(
+            conjunction(&lexer.conditions()),
+            "CI is not defined and ENV=dev"
+        );
+    }
+}
@@ -1,11 +1,57 @@
-use crate::{generator::generate, parser::parse};
+use crate::{
+    config::Config,
+    diagnostic::{Diagnostic, DiagnosticKind, Severity},
+    parser::{Tags, parse_with_diagnostics},
+    render::{Format, Renderer},
+    validate::validate,
+};
 
 pub mod anchor;
+pub mod completion;
+pub mod config;
+pub mod d2;
+pub mod diagnostic;
+pub mod dot;
 pub mod generator;
+pub mod graph;
+pub mod help_target;
+pub mod html;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod lexer;
+pub mod man;
+pub mod mermaid;
 pub mod model;
 pub mod parser;
+pub mod plantuml;
+pub mod render;
+pub mod terminal;
+pub mod validate;
+pub mod workflow;
 
 pub fn process(content: &str) -> String {
-    let doc = parse(content);
-    generate(&doc)
+    process_with_config(content, &Config::default()).0
+}
+
+pub fn process_with_config(content: &str, config: &Config) -> (String, Vec<Diagnostic>) {
+    process_with_renderer(content, config, Format::Markdown.renderer(None).as_ref())
+}
+
+pub fn process_with_renderer(
+    content: &str,
+    config: &Config,
+    renderer: &dyn Renderer,
+) -> (String, Vec<Diagnostic>) {
+    let (doc, mut diagnostics) = parse_with_diagnostics(content, &Tags::new(config));
+    diagnostics.extend(validate(&doc));
+
+    if config.strict {
+        for diagnostic in &mut diagnostics {
+            if diagnostic.kind == DiagnosticKind::OrphanAnnotation {
+                diagnostic.severity = Severity::Error;
+            }
+        }
+    }
+
+    (renderer.render(&doc, config), diagnostics)
 }
@@ -1,5 +1,8 @@
-use clap::Parser;
-use makefile2doc::process;
+use clap::{Parser, Subcommand};
+use makefile2doc::config::{CONFIG_FILE_NAME, Config};
+use makefile2doc::help_target::{self, HELP_FILE_NAME, HelpTargetRenderer};
+use makefile2doc::process_with_renderer;
+use makefile2doc::render::{Format, Renderer};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process;
@@ -7,16 +10,71 @@ use std::process;
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short = 'i', long, default_value = "Makefile")]
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    #[arg(short = 'i', long, default_value = "Makefile", global = true)]
     input: PathBuf,
 
-    #[arg(short = 'o', long)]
+    #[arg(short = 'o', long, global = true)]
     output: Option<PathBuf>,
+
+    /// Output format
+    #[arg(short = 'f', long, value_enum, default_value_t = Format::Markdown)]
+    format: Format,
+
+    /// Config file (defaults to makefile2doc.toml next to the Makefile, if any)
+    #[arg(short = 'c', long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Comment marker that starts an annotation (default: "##")
+    #[arg(long, global = true)]
+    comment_marker: Option<String>,
+
+    /// Sigil placed before tag names (default: "@")
+    #[arg(long, global = true)]
+    tag_sigil: Option<String>,
+
+    /// Treat annotations that are not attached to a rule as errors
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Add a dependency graph under each command, limited to this depth
+    #[arg(long, value_name = "DEPTH")]
+    focus_depth: Option<usize>,
+
+    /// Hide the arrows of the workflow graph that are implied by other arrows
+    #[arg(long)]
+    transitive_reduction: bool,
+
+    /// Project name used by the man page (default: the Makefile's directory)
+    #[arg(long)]
+    project: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Print the JSON Schema of the `--format json` output
+    #[cfg(feature = "json")]
+    Schema,
+
+    /// Write a `help` target in plain make, to help.mk or into the Makefile
+    HelpTarget {
+        /// Write the target into a marked region of the Makefile itself
+        #[arg(long)]
+        inline: bool,
+    },
 }
 
 fn main() {
     let args = Args::parse();
 
+    #[cfg(feature = "json")]
+    if let Some(Commands::Schema) = args.command {
+        print!("{}", makefile2doc::json::schema());
+        return;
+    }
+
     let content = match fs::read_to_string(&args.input) {
         Ok(c) => c,
         Err(e) => {
@@ -26,28 +84,153 @@ fn main() {
         }
     };
 
-    let markdown = process(&content);
+    let config = load_config(&args);
+
+    if let Some(Commands::HelpTarget { inline }) = args.command {
+        write_help_target(&args, &content, &config, inline);
+        return;
+    }
 
     let output_path = match args.output {
-        Some(path) => path,
-        None => {
+        Some(path) => Some(path),
+        None => args.format.default_file_name(&config).map(|file_name| {
             let parent = args.input.parent().unwrap_or(Path::new("."));
-            parent.join("MAKEFILE.md")
-        }
+            parent.join(file_name)
+        }),
     };
 
-    if let Err(e) = fs::write(&output_path, markdown) {
-        eprintln!(
-            "Error: Unable to write the file '{}'",
-            output_path.display()
-        );
+    let renderer = args.format.renderer(output_path.as_deref());
+    let document = render(&content, &config, renderer.as_ref());
+
+    let Some(output_path) = output_path else {
+        print!("{}", document);
+        return;
+    };
+
+    write(&output_path, &document);
+
+    println!(
+        "Successfully generated documentation at {}",
+        output_path.display()
+    );
+}
+
+fn render(content: &str, config: &Config, renderer: &dyn Renderer) -> String {
+    let (document, diagnostics) = process_with_renderer(content, config, renderer);
+
+    for diagnostic in &diagnostics {
+        eprintln!("{}", diagnostic);
+    }
+
+    if diagnostics.iter().any(|d| d.is_error()) {
+        process::exit(1);
+    }
+
+    document
+}
+
+fn write(path: &Path, content: &str) {
+    if let Err(e) = fs::write(path, content) {
+        eprintln!("Error: Unable to write the file '{}'", path.display());
 
         eprintln!("Details: {}", e);
         process::exit(1);
     }
+}
+
+fn write_help_target(args: &Args, content: &str, config: &Config, inline: bool) {
+    let stripped = help_target::strip_region(content).unwrap_or_else(|e| {
+        eprintln!("Error: {} in '{}'", e, args.input.display());
+        process::exit(1);
+    });
+    let region = render(&stripped, config, &HelpTargetRenderer);
+
+    let (path, previous, updated) = if inline {
+        let updated = help_target::update_region(content, &region)
+            .expect("the region markers were checked by strip_region");
+        (args.input.clone(), content.to_string(), updated)
+    } else {
+        let path = match &args.output {
+            Some(path) => path.clone(),
+            None => {
+                let parent = args.input.parent().unwrap_or(Path::new("."));
+                parent.join(HELP_FILE_NAME)
+            }
+        };
+        let previous = fs::read_to_string(&path).unwrap_or_default();
+        (path, previous, region)
+    };
+
+    if previous == updated {
+        println!("The help target in {} is up to date", path.display());
+        return;
+    }
 
+    write(&path, &updated);
     println!(
-        "Successfully generated documentation at {}",
-        output_path.display()
+        "Successfully generated the help target in {}",
+        path.display()
     );
+
+    if !inline {
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        if !content.contains(file_name.as_ref()) {
+            println!("Add `include {}` to your Makefile to use it", file_name);
+        }
+    }
+}
+
+fn load_config(args: &Args) -> Config {
+    let path = match &args.config {
+        Some(path) => Some(path.clone()),
+        None => {
+            let parent = args.input.parent().unwrap_or(Path::new("."));
+            Some(parent.join(CONFIG_FILE_NAME)).filter(|p| p.is_file())
+        }
+    };
+
+    let mut config = match path {
+        Some(path) => match Config::from_file(&path) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Error: Unable to load the config '{}'", path.display());
+                eprintln!("Details: {}", e);
+                process::exit(1);
+            }
+        },
+        None => Config::default(),
+    };
+
+    if let Some(marker) = &args.comment_marker {
+        config.comment_marker = marker.clone();
+    }
+    if let Some(sigil) = &args.tag_sigil {
+        config.tag_sigil = sigil.clone();
+    }
+    if args.strict {
+        config.strict = true;
+    }
+    if let Some(depth) = args.focus_depth {
+        config.focus_depth = Some(depth);
+    }
+    if args.transitive_reduction {
+        config.transitive_reduction = true;
+    }
+    if let Some(project) = &args.project {
+        config.project = Some(project.clone());
+    }
+    if let Err(e) = config.validate() {
+        eprintln!("Error: Invalid configuration");
+        eprintln!("Details: {}", e);
+        process::exit(1);
+    }
+    if config.project.is_none() {
+        config.project = args
+            .input
+            .canonicalize()
+            .ok()
+            .and_then(|path| Some(path.parent()?.file_name()?.to_string_lossy().into_owned()));
+    }
+
+    config
 }
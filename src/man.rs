@@ -0,0 +1,211 @@
+use crate::{
+    config::Config,
+    lexer::conjunction,
+    model::{MakefileDoc, TagValue},
+    render::Renderer,
+};
+
+/// Name of the man page, `make-<project>`.
+pub fn page_name(config: &Config) -> String {
+    format!("make-{}", config.project.as_deref().unwrap_or("project"))
+}
+
+pub struct ManRenderer;
+
+impl Renderer for ManRenderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        let name = page_name(config);
+        let project = config.project.as_deref().unwrap_or("the project");
+        let mut man = String::new();
+
+        man.push_str(&format!(
+            ".TH \"{}\" 7 \"\" \"makefile2doc\" \"Makefile Documentation\"\n",
+            escape(&name.to_uppercase())
+        ));
+        man.push_str(".SH NAME\n");
+        man.push_str(&format!(
+            "{} \\- make targets of {}\n",
+            escape(&name),
+            escape(project)
+        ));
+        man.push_str(".SH SYNOPSIS\n.B make\n.I target\n");
+        man.push_str(".SH DESCRIPTION\n");
+        man.push_str(&format!(
+            "The targets documented in the Makefile of {}, grouped by category.\n",
+            escape(project)
+        ));
+
+        for cat in &doc.categories {
+            man.push_str(&format!(".SH \"{}\"\n", escape(&cat.name.to_uppercase())));
+
+            for cmd in &cat.commands {
+                man.push_str(&format!(".TP\n.B make {}\n", escape(&cmd.name)));
+                if cmd.branches.is_empty() {
+                    man.push_str(&format_description(&cmd.description));
+                }
+                for (i, branch) in cmd.branches.iter().enumerate() {
+                    if i > 0 {
+                        man.push_str(".br\n");
+                    }
+                    if !branch.conditions.is_empty() {
+                        let alternatives: Vec<String> =
+                            branch.conditions.iter().map(|c| conjunction(c)).collect();
+                        man.push_str(&format!(
+                            "When {}:\n.br\n",
+                            format_list(&alternatives, " or ")
+                        ));
+                    }
+                    man.push_str(&format_description(&branch.description));
+                }
+
+                if !cmd.conditions.is_empty() {
+                    let alternatives: Vec<String> =
+                        cmd.conditions.iter().map(|c| conjunction(c)).collect();
+                    man.push_str(&format!(
+                        ".br\nOnly when {}.\n",
+                        format_list(&alternatives, " or ")
+                    ));
+                }
+                if !cmd.variables.is_empty() {
+                    let variables: Vec<String> =
+                        cmd.variables.iter().map(|v| v.to_string()).collect();
+                    man.push_str(&format!(".br\nSets {}.\n", format_list(&variables, ", ")));
+                }
+                if !cmd.dependencies.is_empty() {
+                    man.push_str(&format!(
+                        ".br\nDependencies: {}\n",
+                        format_list(&cmd.dependencies, ", ")
+                    ));
+                }
+                if !cmd.env.is_empty() {
+                    man.push_str(&format!(
+                        ".br\nRequired variables: {}\n",
+                        format_list(&cmd.env, ", ")
+                    ));
+                }
+                let params: Vec<String> = cmd
+                    .params()
+                    .into_iter()
+                    .map(|(name, explanation)| match explanation {
+                        Some(explanation) => {
+                            format!("\\fB{}\\fR ({})", escape(name), escape(explanation))
+                        }
+                        None => format!("\\fB{}\\fR", escape(name)),
+                    })
+                    .collect();
+                if !params.is_empty() {
+                    man.push_str(&format!(".br\nParameters: {}\n", params.join(", ")));
+                }
+                for tag in &config.tags {
+                    let value = match cmd.tags.get(&tag.name) {
+                        Some(TagValue::Text(text)) => escape(text),
+                        Some(TagValue::List(items)) => format_list(items, ", "),
+                        None => continue,
+                    };
+                    man.push_str(&format!(".br\n{}: {}\n", escape(tag.title()), value));
+                }
+            }
+        }
+
+        if !doc.patterns.is_empty() {
+            man.push_str(".SH \"PATTERN RULES\"\n");
+            for pattern in &doc.patterns {
+                man.push_str(&format!(".TP\n.B {}\n", escape(&pattern.pattern)));
+                man.push_str(&format_description(&pattern.description));
+                if !pattern.prerequisites.is_empty() {
+                    man.push_str(&format!(
+                        ".br\nPrerequisites: {}\n",
+                        format_list(&pattern.prerequisites, ", ")
+                    ));
+                }
+                if !pattern.env.is_empty() {
+                    man.push_str(&format!(
+                        ".br\nRequired variables: {}\n",
+                        format_list(&pattern.env, ", ")
+                    ));
+                }
+            }
+        }
+
+        man.push_str(".SH \"SEE ALSO\"\n.BR make (1)\n");
+        man
+    }
+}
+
+fn format_description(desc: &str) -> String {
+    if desc.is_empty() {
+        return String::from("No description.\n");
+    }
+
+    desc.split("\\n")
+        .map(|line| escape(line.trim()))
+        .collect::<Vec<_>>()
+        .join("\n.br\n")
+        + "\n"
+}
+
+fn format_list(items: &[String], separator: &str) -> String {
+    items
+        .iter()
+        .map(|item| format!("\\fB{}\\fR", escape(item)))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Escapes text for roff, including control characters at the start of a line.
+fn escape(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\e")
+        .replace('-', "\\-")
+        .replace('"', "\\(dq");
+
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn escape_protects_roff_syntax() {
+        assert_eq!(escape("shell-back"), "shell\\-back");
+        assert_eq!(escape(".env file"), "\\&.env file");
+        assert_eq!(escape("C:\\path \"x\""), "C:\\epath \\(dqx\\(dq");
+    }
+
+    #[test]
+    fn renders_a_section_per_category_and_a_paragraph_per_command() {
+        let doc = parse(
+            r#"
+                ## @category Database
+                ## @description Run the migrations\nThen seed
+                ## @depends up
+                ## @env DATABASE_URL
+                ## @param STEP the number of migrations, DRY_RUN
+                migrate:
+            "#,
+        );
+        let config = Config {
+            project: Some(String::from("shop")),
+            ..Config::default()
+        };
+
+        let man = ManRenderer.render(&doc, &config);
+
+        assert!(man.starts_with(".TH \"MAKE\\-SHOP\" 7 "));
+        assert!(man.contains(".SH NAME\nmake\\-shop \\- make targets of shop\n"));
+        assert!(man.contains(concat!(
+            ".SH \"DATABASE\"\n",
+            ".TP\n.B make migrate\n",
+            "Run the migrations\n.br\nThen seed\n",
+            ".br\nDependencies: \\fBup\\fR\n",
+            ".br\nRequired variables: \\fBDATABASE_URL\\fR\n",
+            ".br\nParameters: \\fBSTEP\\fR (the number of migrations), \\fBDRY_RUN\\fR\n",
+        )));
+    }
+}
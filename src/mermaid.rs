@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use crate::{anchor::IdAllocator, model::MakefileDoc};
+
+/// Mermaid node and subgraph identifiers for a document. Identifiers are
+/// prefixed so they never collide with Mermaid keywords (`end`, `graph`...)
+/// and only contain characters Mermaid accepts unquoted.
+#[derive(Debug, Clone)]
+pub struct MermaidIds {
+    category_ids: Vec<String>,
+    pattern_ids: Vec<String>,
+    node_ids: HashMap<String, String>,
+}
+
+impl MermaidIds {
+    pub fn build(doc: &MakefileDoc) -> Self {
+        let mut allocator = IdAllocator::new('_', sanitize);
+
+        let category_ids = doc
+            .categories
+            .iter()
+            .map(|cat| allocator.next_id("cat", &cat.name))
+            .collect();
+
+        let pattern_ids = (0..doc.patterns.len())
+            .map(|i| allocator.next_id("pattern", &i.to_string()))
+            .collect();
+
+        let mut node_ids = HashMap::new();
+        let names = doc
+            .commands()
+            .map(|cmd| &cmd.name)
+            .chain(
+                doc.commands()
+                    .flat_map(|cmd| cmd.dependencies.iter().chain(&cmd.prerequisites)),
+            )
+            .chain(doc.patterns.iter().flat_map(|p| &p.dependencies));
+
+        for name in names {
+            if !node_ids.contains_key(name) {
+                node_ids.insert(name.clone(), allocator.next_id("cmd", name));
+            }
+        }
+
+        Self {
+            category_ids,
+            pattern_ids,
+            node_ids,
+        }
+    }
+
+    pub fn category_id(&self, cat_idx: usize) -> &str {
+        self.category_ids[cat_idx].as_str()
+    }
+
+    pub fn pattern_id(&self, pattern_idx: usize) -> &str {
+        self.pattern_ids[pattern_idx].as_str()
+    }
+
+    pub fn node_id(&self, name: &str) -> &str {
+        self.node_ids
+            .get(name)
+            .map(String::as_str)
+            .expect("every target of the document has a Mermaid id")
+    }
+}
+
+fn sanitize(text: &str) -> String {
+    let mut id = String::with_capacity(text.len());
+    let mut last_was_underscore = false;
+
+    for c in text.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c);
+            last_was_underscore = false;
+        } else if !id.is_empty() && !last_was_underscore {
+            id.push('_');
+            last_was_underscore = true;
+        }
+    }
+
+    if id.ends_with('_') {
+        id.pop();
+    }
+
+    id
+}
+
+/// Quotes a label so that Mermaid renders it verbatim.
+pub fn label(text: &str) -> String {
+    let escaped = text
+        .replace('#', "#35;")
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;");
+
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn ids_avoid_reserved_words_and_special_characters() {
+        let content = r#"
+                ## @category C++ / Tools
+                ## @description End
+                end:
+
+                ## @description Graph
+                ## @depends docs.pdf, end
+                graph:
+
+                ## @category C++ | Tools
+                ## @description Dotted
+                build-all:
+            "#;
+
+        let doc = parse(content);
+        let ids = MermaidIds::build(&doc);
+
+        assert_eq!(ids.category_id(0), "cat_C_Tools");
+        assert_eq!(ids.category_id(1), "cat_C_Tools_2");
+        assert_eq!(ids.node_id("end"), "cmd_end");
+        assert_eq!(ids.node_id("graph"), "cmd_graph");
+        assert_eq!(ids.node_id("build-all"), "cmd_build_all");
+        assert_eq!(ids.node_id("docs.pdf"), "cmd_docs_pdf");
+    }
+
+    #[test]
+    fn labels_are_quoted_and_escaped() {
+        assert_eq!(label("C++ / Tools"), "\"C++ / Tools\"");
+        assert_eq!(label("say \"hi\""), "\"say #quot;hi#quot;\"");
+        assert_eq!(label("<b>#1</b>"), "\"#lt;b#gt;#35;1#lt;/b#gt;\"");
+    }
+}
@@ -1,18 +1,134 @@
+use std::collections::BTreeMap;
+
+use crate::{config::PARAM_TAG, lexer::Condition};
+
+#[cfg(feature = "json")]
+use schemars::JsonSchema;
+#[cfg(feature = "json")]
+use serde::Serialize;
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
 pub struct MakefileDoc {
     pub categories: Vec<Category>,
+    pub patterns: Vec<PatternRule>,
+    pub targets: Vec<String>,
+}
+
+impl MakefileDoc {
+    pub fn commands(&self) -> impl Iterator<Item = &Command> {
+        self.categories.iter().flat_map(|cat| cat.commands.iter())
+    }
+
+    pub fn find_command(&self, name: &str) -> Option<&Command> {
+        self.commands().find(|cmd| cmd.name == name)
+    }
+
+    /// The commands listing `name` in their `@depends`, the reverse of the
+    /// Dependencies column.
+    pub fn dependents<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Command> {
+        self.commands()
+            .filter(move |cmd| cmd.dependencies.iter().any(|dep| dep == name))
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
 pub struct Category {
     pub name: String,
     pub commands: Vec<Command>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
 pub struct Command {
     pub name: String,
     pub description: String,
     pub dependencies: Vec<String>,
+    pub prerequisites: Vec<String>,
+    pub env: Vec<String>,
+    /// Alternatives under which the target is defined, each one a list of
+    /// conditions that must all hold. Empty when it is always defined.
+    pub conditions: Vec<Vec<Condition>>,
+    /// The description given in each branch of a conditional, when the
+    /// branches document the target differently.
+    pub branches: Vec<Branch>,
+    pub variables: Vec<Variable>,
+    pub tags: BTreeMap<String, TagValue>,
+}
+
+impl Command {
+    /// The `@param` entries, as the variable name and the explanation that
+    /// may follow it.
+    pub fn params(&self) -> Vec<(&str, Option<&str>)> {
+        let Some(TagValue::List(items)) = self.tags.get(PARAM_TAG) else {
+            return Vec::new();
+        };
+
+        items
+            .iter()
+            .filter_map(|item| {
+                let mut parts = item.trim().splitn(2, char::is_whitespace);
+                let name = parts.next().filter(|name| !name.is_empty())?;
+                Some((name, parts.next().map(str::trim)))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
+pub struct Branch {
+    pub conditions: Vec<Vec<Condition>>,
+    pub description: String,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
+#[cfg_attr(feature = "json", serde(untagged))]
+pub enum TagValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
+pub struct Variable {
+    pub name: String,
+    pub operator: String,
+    pub value: String,
+}
+
+impl std::fmt::Display for Variable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.name, self.operator, self.value)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "json", derive(Serialize, JsonSchema))]
+pub struct PatternRule {
+    pub targets: Option<String>,
+    pub pattern: String,
+    pub prerequisites: Vec<String>,
+    pub description: String,
+    pub dependencies: Vec<String>,
     pub env: Vec<String>,
 }
+
+impl PatternRule {
+    pub fn matches(&self, name: &str) -> bool {
+        match self.pattern.split_once('%') {
+            Some((prefix, suffix)) => {
+                name.len() > prefix.len() + suffix.len()
+                    && name.starts_with(prefix)
+                    && name.ends_with(suffix)
+            }
+            None => false,
+        }
+    }
+
+    pub fn apply(pattern: &str, stem: &str) -> String {
+        pattern.replacen('%', stem, 1)
+    }
+}
@@ -1,11 +1,21 @@
-use crate::model::{Category, Command, MakefileDoc};
+use crate::{
+    config::{BUILTIN_TAGS, Config, CustomTag, PARAM_TAG},
+    diagnostic::{Diagnostic, DiagnosticKind, suggest},
+    lexer::{Condition, Lexer, Line, LineKind},
+    model::{Branch, Category, Command, MakefileDoc, PatternRule, TagValue, Variable},
+};
 use lazy_regex::regex;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 struct ParserContext {
     current_category: String,
     buffer_desc: String,
     buffer_deps: Vec<String>,
     buffer_envs: Vec<String>,
+    buffer_tags: BTreeMap<String, TagValue>,
+    block_start: Option<usize>,
+    interrupted_at: Option<usize>,
 }
 
 impl ParserContext {
@@ -15,6 +25,23 @@ impl ParserContext {
             buffer_desc: String::new(),
             buffer_deps: Vec::new(),
             buffer_envs: Vec::new(),
+            buffer_tags: BTreeMap::new(),
+            block_start: None,
+            interrupted_at: None,
+        }
+    }
+
+    fn has_metadata(&self) -> bool {
+        self.block_start.is_some()
+    }
+
+    fn start_block(&mut self, number: usize) {
+        self.block_start.get_or_insert(number);
+    }
+
+    fn interrupt(&mut self, number: usize) {
+        if self.has_metadata() {
+            self.interrupted_at.get_or_insert(number);
         }
     }
 
@@ -22,39 +49,136 @@ impl ParserContext {
         self.buffer_deps.clear();
         self.buffer_desc.clear();
         self.buffer_envs.clear();
+        self.buffer_tags.clear();
+        self.block_start = None;
+        self.interrupted_at = None;
     }
 }
 
-fn try_extract_category(line: &str) -> Option<String> {
-    regex!(r"^##\s@category\s+(.*)$")
-        .captures(line)
-        .map(|captures| captures[1].trim().to_string())
+pub struct Tags {
+    category: Regex,
+    description: Regex,
+    depends: Regex,
+    env: Regex,
+    custom: Vec<(CustomTag, Regex)>,
+    any: Option<Regex>,
+    sigil: String,
 }
 
-fn try_extract_description(line: &str) -> Option<String> {
-    regex!(r"^##\s@description\s+(.*)$")
-        .captures(line)
-        .map(|captures| captures[1].trim().to_string())
-}
+impl Tags {
+    pub fn new(config: &Config) -> Self {
+        let tag = |name: &str| {
+            Regex::new(&format!(
+                r"^{}\s*{}{}\s+(.*)$",
+                regex::escape(&config.comment_marker),
+                regex::escape(&config.tag_sigil),
+                name
+            ))
+            .expect("escaped tag syntax is always a valid regex")
+        };
 
-fn try_extract_depends(line: &str) -> Option<Vec<String>> {
-    regex!(r"^##\s@depends\s+(.*)$")
-        .captures(line)
-        .map(|captures| {
+        Self {
+            category: tag("category"),
+            description: tag("description"),
+            depends: tag("depends"),
+            env: tag("env"),
+            custom: std::iter::once(CustomTag {
+                name: String::from(PARAM_TAG),
+                list: true,
+                title: None,
+            })
+            .chain(config.tags.iter().cloned())
+            .map(|custom| {
+                let regex = tag(&custom.name);
+                (custom, regex)
+            })
+            .collect(),
+            any: (!config.tag_sigil.is_empty()).then(|| {
+                Regex::new(&format!(
+                    r"^{}\s*{}([A-Za-z0-9_-]+)",
+                    regex::escape(&config.comment_marker),
+                    regex::escape(&config.tag_sigil)
+                ))
+                .expect("escaped tag syntax is always a valid regex")
+            }),
+            sigil: config.tag_sigil.clone(),
+        }
+    }
+
+    fn try_extract_category(&self, line: &str) -> Option<String> {
+        self.category
+            .captures(line)
+            .map(|captures| captures[1].trim().to_string())
+    }
+
+    fn try_extract_description(&self, line: &str) -> Option<String> {
+        self.description
+            .captures(line)
+            .map(|captures| captures[1].trim().to_string())
+    }
+
+    fn try_extract_depends(&self, line: &str) -> Option<Vec<String>> {
+        self.depends.captures(line).map(|captures| {
             captures[1]
                 .split(",")
                 .map(|split| split.trim().to_string())
                 .collect()
         })
-}
+    }
+
+    fn try_extract_envs(&self, line: &str) -> Option<Vec<String>> {
+        self.env.captures(line).map(|captures| {
+            captures[1]
+                .split(",")
+                .map(|split| split.trim().to_string())
+                .collect()
+        })
+    }
 
-fn try_extract_envs(line: &str) -> Option<Vec<String>> {
-    regex!(r"^##\s@env\s+(.*)$").captures(line).map(|captures| {
-        captures[1]
-            .split(",")
-            .map(|split| split.trim().to_string())
+    fn try_extract_custom(&self, line: &str) -> Option<(String, TagValue)> {
+        self.custom.iter().find_map(|(custom, regex)| {
+            regex.captures(line).map(|captures| {
+                let value = if custom.list {
+                    TagValue::List(
+                        captures[1]
+                            .split(",")
+                            .map(|split| split.trim().to_string())
+                            .collect(),
+                    )
+                } else {
+                    TagValue::Text(captures[1].trim().to_string())
+                };
+                (custom.name.clone(), value)
+            })
+        })
+    }
+
+    fn try_extract_unknown(&self, line: &str) -> Option<String> {
+        let name = self.any.as_ref()?.captures(line)?[1].to_string();
+        let known = BUILTIN_TAGS.contains(&name.as_str())
+            || self.custom.iter().any(|(custom, _)| custom.name == name);
+
+        (!known).then_some(name)
+    }
+
+    fn known_names(&self) -> Vec<&str> {
+        BUILTIN_TAGS
+            .iter()
+            .copied()
+            .chain(
+                self.custom
+                    .iter()
+                    .map(|(custom, _)| custom.name.as_str())
+                    .filter(|name| *name != PARAM_TAG),
+            )
             .collect()
-    })
+    }
+}
+
+impl Default for Tags {
+    fn default() -> Self {
+        Self::new(&Config::default())
+    }
 }
 
 fn try_extract_target(line: &str) -> Option<String> {
@@ -63,45 +187,284 @@ fn try_extract_target(line: &str) -> Option<String> {
         .map(|captures| captures[1].trim().to_string())
 }
 
+fn is_special_target(line: &str) -> bool {
+    regex!(r"^\.[A-Z_]+\s*:").is_match(line)
+}
+
+fn try_extract_double_colon(line: &str) -> Option<String> {
+    regex!(r"^([a-zA-Z0-9_-]+)::")
+        .captures(line)
+        .map(|captures| captures[1].trim().to_string())
+}
+
+fn try_extract_target_variable(line: &str) -> Option<(Vec<String>, Variable)> {
+    regex!(r"^([^:=]+?)::?\s*(?:(?:export|override|private)\s+)*([A-Za-z_][A-Za-z0-9_.-]*)\s*(\+=|\?=|!=|:::=|::=|:=|=)\s*(.*)$")
+        .captures(line)
+        .map(|captures| {
+            let targets = captures[1]
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+            let variable = Variable {
+                name: captures[2].to_string(),
+                operator: captures[3].to_string(),
+                value: captures[4].trim().to_string(),
+            };
+            (targets, variable)
+        })
+}
+
+/// The pattern rules of a rule line, one per target pattern: a grouped rule
+/// like `%.tab.c %.tab.h: %.y` builds both.
+fn try_extract_patterns(line: &str) -> Option<Vec<PatternRule>> {
+    let header = line.split(';').next().unwrap_or(line);
+    let parts = split_rule_header(header);
+
+    // `%.o: CFLAGS += -O2` sets a pattern-specific variable, it is not a rule.
+    if (1..parts.len()).any(|i| is_variable_assignment(&parts[i..].join(":"))) {
+        return None;
+    }
+
+    let (targets, pattern, prerequisites) = match parts.as_slice() {
+        [targets, pattern, prerequisites] => (Some(targets.trim()), pattern, prerequisites),
+        [pattern, prerequisites] => (None, pattern, prerequisites),
+        _ => return None,
+    };
+
+    let patterns: Vec<&str> = pattern.split_whitespace().collect();
+    if patterns.is_empty() || !patterns.iter().all(|p| p.contains('%')) {
+        return None;
+    }
+
+    let prerequisites: Vec<String> = prerequisites
+        .split_whitespace()
+        .filter(|p| *p != "|")
+        .map(str::to_string)
+        .collect();
+
+    Some(
+        patterns
+            .into_iter()
+            .map(|pattern| PatternRule {
+                targets: targets.map(str::to_string),
+                pattern: pattern.to_string(),
+                prerequisites: prerequisites.clone(),
+                description: String::new(),
+                dependencies: Vec::new(),
+                env: Vec::new(),
+            })
+            .collect(),
+    )
+}
+
+fn is_variable_assignment(text: &str) -> bool {
+    regex!(r"^\s*(?:(?:export|override|private)\s+)*[A-Za-z_][A-Za-z0-9_.-]*\s*(?:\+=|\?=|!=|:::=|::=|:=|=)")
+        .is_match(text)
+}
+
+fn extract_rule_targets(line: &str) -> Vec<String> {
+    split_rule_header(line)[0]
+        .split_whitespace()
+        .filter(|target| !target.contains(['%', '$']))
+        .map(str::to_string)
+        .collect()
+}
+
+fn extract_prerequisites(line: &str) -> Vec<String> {
+    let header = line.split(';').next().unwrap_or(line);
+    let parts = split_rule_header(header);
+
+    if parts.len() < 2 {
+        return Vec::new();
+    }
+
+    parts[parts.len() - 1]
+        .split_whitespace()
+        .filter(|prereq| *prereq != "|" && !prereq.contains(['%', '$']))
+        .map(str::to_string)
+        .collect()
+}
+
+fn split_rule_header(header: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in header.char_indices() {
+        match c {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth = depth.saturating_sub(1),
+            ':' if depth == 0 => {
+                parts.push(&header[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&header[start..]);
+    parts
+}
+
 pub fn parse(content: &str) -> MakefileDoc {
+    parse_with(content, &Tags::default())
+}
+
+pub fn parse_with(content: &str, tags: &Tags) -> MakefileDoc {
+    parse_with_diagnostics(content, tags).0
+}
+
+pub fn parse_with_diagnostics(content: &str, tags: &Tags) -> (MakefileDoc, Vec<Diagnostic>) {
     let lines = match clean_content(content) {
         Ok(l) => l,
-        Err(_) => return MakefileDoc { categories: vec![] },
+        Err(_) => {
+            let doc = MakefileDoc {
+                categories: vec![],
+                patterns: vec![],
+                targets: vec![],
+            };
+            return (doc, vec![]);
+        }
     };
 
     let mut categories: Vec<Category> = Vec::new();
+    let mut patterns: Vec<PatternRule> = Vec::new();
+    let mut targets: Vec<String> = Vec::new();
+    let mut double_colons: HashSet<String> = HashSet::new();
+    let mut target_variables: HashMap<String, Vec<Variable>> = HashMap::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
     let mut ctx = ParserContext::new();
 
-    for line in lines {
-        let line = line.trim();
+    let mut lexer = Lexer::new(lines);
 
-        if let Some(cat) = try_extract_category(line) {
-            if categories.iter().any(|c| c.name == cat) {
-                eprint!(
-                    "Warning: The category '{}' is defined multiple times, you should consider combining them.",
-                    cat
-                )
+    while let Some(Line {
+        number,
+        text: line,
+        kind,
+    }) = lexer.next()
+    {
+        if kind == LineKind::Comment {
+            if let Some(cat) = tags.try_extract_category(line) {
+                if categories.iter().any(|c| c.name == cat) {
+                    diagnostics.push(Diagnostic::warning(
+                        DiagnosticKind::DuplicateCategory,
+                        Some(number),
+                        format!(
+                            "The category '{}' is defined multiple times, you should consider combining them.",
+                            cat
+                        ),
+                    ));
+                }
+                ctx.interrupt(number);
+                ctx.current_category = cat;
+                continue;
+            }
+
+            if let Some(desc) = tags.try_extract_description(line) {
+                ctx.buffer_desc.push_str(&desc);
+                ctx.start_block(number);
+                continue;
+            }
+
+            if let Some(deps) = tags.try_extract_depends(line) {
+                ctx.buffer_deps.extend(deps);
+                ctx.start_block(number);
+                continue;
+            }
+
+            if let Some(envs) = tags.try_extract_envs(line) {
+                ctx.buffer_envs.extend(envs);
+                ctx.start_block(number);
+                continue;
+            }
+
+            if let Some((name, value)) = tags.try_extract_custom(line) {
+                match (ctx.buffer_tags.get_mut(&name), value) {
+                    (Some(TagValue::List(items)), TagValue::List(more)) => items.extend(more),
+                    (_, value) => {
+                        ctx.buffer_tags.insert(name, value);
+                    }
+                }
+                ctx.start_block(number);
+                continue;
+            }
+
+            if let Some(name) = tags.try_extract_unknown(line) {
+                let message = match suggest(&name, &tags.known_names()) {
+                    Some(known) => format!(
+                        "Unknown tag `{}{}`, did you mean `{}{}`?",
+                        tags.sigil, name, tags.sigil, known
+                    ),
+                    None => format!("Unknown tag `{}{}`", tags.sigil, name),
+                };
+                diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::UnknownTag,
+                    Some(number),
+                    message,
+                ));
             }
-            ctx.current_category = cat;
             continue;
         }
 
-        if let Some(desc) = try_extract_description(line) {
-            ctx.buffer_desc.push_str(&desc);
+        if kind != LineKind::Rule {
+            if matches!(
+                kind,
+                LineKind::Assignment
+                    | LineKind::Conditional
+                    | LineKind::Directive
+                    | LineKind::Other
+            ) {
+                ctx.interrupt(number);
+            }
             continue;
         }
 
-        if let Some(deps) = try_extract_depends(line) {
-            ctx.buffer_deps.extend(deps);
+        if let Some((targets, variable)) = try_extract_target_variable(line) {
+            for target in targets {
+                target_variables
+                    .entry(target)
+                    .or_default()
+                    .push(variable.clone());
+            }
             continue;
         }
 
-        if let Some(envs) = try_extract_envs(line) {
-            ctx.buffer_envs.extend(envs);
+        for target in extract_rule_targets(line) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        // `.PHONY: build` and the like often sit between the annotations and
+        // their target, they keep the buffered metadata.
+        if is_special_target(line) {
+            continue;
+        }
+
+        let rule_name = try_extract_patterns(line)
+            .map(|patterns| patterns[0].pattern.clone())
+            .or_else(|| try_extract_target(line));
+        if let Some(diagnostic) = check_attachment(&ctx, tags, rule_name.as_deref()) {
+            diagnostics.push(diagnostic);
+        }
+
+        if let Some(grouped) = try_extract_patterns(line) {
+            if !ctx.buffer_desc.is_empty() {
+                for mut pattern in grouped {
+                    pattern.description = ctx.buffer_desc.clone();
+                    pattern.dependencies = ctx.buffer_deps.clone();
+                    pattern.env = ctx.buffer_envs.clone();
+                    patterns.push(pattern);
+                }
+            }
+
+            ctx.clear_metadata();
             continue;
         }
 
         if let Some(target_name) = try_extract_target(line) {
+            let is_double_colon = try_extract_double_colon(line).is_some();
+
             if ctx.buffer_desc.is_empty() {
                 ctx.clear_metadata();
                 continue;
@@ -111,10 +474,28 @@ pub fn parse(content: &str) -> MakefileDoc {
                 name: target_name,
                 description: ctx.buffer_desc.clone(),
                 dependencies: ctx.buffer_deps.clone(),
+                prerequisites: extract_prerequisites(line),
                 env: ctx.buffer_envs.clone(),
+                conditions: Some(lexer.conditions())
+                    .filter(|conditions| !conditions.is_empty())
+                    .into_iter()
+                    .collect(),
+                branches: Vec::new(),
+                variables: Vec::new(),
+                tags: ctx.buffer_tags.clone(),
             };
 
-            if let Some(cat) = categories
+            let merges_part = is_double_colon && !double_colons.insert(command.name.clone());
+
+            if let Some(existing) = categories
+                .iter_mut()
+                .flat_map(|c| c.commands.iter_mut())
+                .find(|c| {
+                    c.name == command.name && (merges_part || c.conditions != command.conditions)
+                })
+            {
+                merge_command(existing, command);
+            } else if let Some(cat) = categories
                 .iter_mut()
                 .find(|c| c.name == ctx.current_category)
             {
@@ -127,18 +508,198 @@ pub fn parse(content: &str) -> MakefileDoc {
             }
 
             ctx.clear_metadata();
+            continue;
+        }
+
+        ctx.clear_metadata();
+    }
+
+    if let Some(start) = ctx.block_start {
+        diagnostics.push(Diagnostic::warning(
+            DiagnosticKind::OrphanAnnotation,
+            Some(start),
+            String::from("These annotations are not attached to any rule"),
+        ));
+    }
+
+    for cmd in categories.iter_mut().flat_map(|c| c.commands.iter_mut()) {
+        if let Some(variables) = target_variables.remove(&cmd.name) {
+            cmd.variables = variables;
+        }
+    }
+
+    let doc = MakefileDoc {
+        categories,
+        patterns,
+        targets,
+    };
+    (doc, diagnostics)
+}
+
+fn check_attachment(ctx: &ParserContext, tags: &Tags, rule: Option<&str>) -> Option<Diagnostic> {
+    let start = ctx.block_start?;
+
+    let message = match (rule, ctx.interrupted_at) {
+        (None, _) => {
+            String::from("These annotations are attached to a rule that cannot be documented")
+        }
+        (Some(rule), _) if ctx.buffer_desc.is_empty() => format!(
+            "These annotations are ignored because '{}' has no `{}description`",
+            rule, tags.sigil
+        ),
+        (Some(rule), Some(at)) => format!(
+            "These annotations are separated from '{}' by unrelated lines (line {})",
+            rule, at
+        ),
+        (Some(_), None) => return None,
+    };
+
+    Some(Diagnostic::warning(
+        DiagnosticKind::OrphanAnnotation,
+        Some(start),
+        message,
+    ))
+}
+
+fn merge_command(existing: &mut Command, other: Command) {
+    let branched = existing.conditions != other.conditions
+        && (existing.description != other.description || !existing.branches.is_empty());
+
+    if branched {
+        if existing.branches.is_empty() {
+            existing.branches.push(Branch {
+                conditions: existing.conditions.clone(),
+                description: existing.description.clone(),
+            });
+        }
+
+        match existing
+            .branches
+            .iter_mut()
+            .find(|branch| branch.description == other.description)
+        {
+            Some(branch) => merge_conditions(&mut branch.conditions, other.conditions.clone()),
+            None => {
+                existing.description =
+                    format!("{} \\n {}", existing.description, other.description);
+                existing.branches.push(Branch {
+                    conditions: other.conditions.clone(),
+                    description: other.description,
+                });
+            }
+        }
+    } else if existing.description != other.description {
+        existing.description = format!("{} \\n {}", existing.description, other.description);
+    }
+
+    for dep in other.dependencies {
+        if !existing.dependencies.contains(&dep) {
+            existing.dependencies.push(dep);
+        }
+    }
+
+    for prereq in other.prerequisites {
+        if !existing.prerequisites.contains(&prereq) {
+            existing.prerequisites.push(prereq);
+        }
+    }
+
+    for env in other.env {
+        if !existing.env.contains(&env) {
+            existing.env.push(env);
+        }
+    }
+
+    for (name, value) in other.tags {
+        match (existing.tags.get_mut(&name), value) {
+            (Some(TagValue::List(items)), TagValue::List(more)) => {
+                for item in more {
+                    if !items.contains(&item) {
+                        items.push(item);
+                    }
+                }
+            }
+            (Some(_), _) => {}
+            (None, value) => {
+                existing.tags.insert(name, value);
+            }
+        }
+    }
+
+    merge_conditions(&mut existing.conditions, other.conditions);
+}
+
+/// Adds the alternatives of `other` to `existing`. An empty list means
+/// "always", and absorbs any other alternative.
+fn merge_conditions(existing: &mut Vec<Vec<Condition>>, other: Vec<Vec<Condition>>) {
+    if existing.is_empty() || other.is_empty() {
+        existing.clear();
+        return;
+    }
+
+    for alternative in other {
+        if !existing.contains(&alternative) {
+            existing.push(alternative);
+        }
+    }
+    *existing = collapse_complements(std::mem::take(existing));
+}
+
+/// Folds alternatives that only differ by one negated condition, like the
+/// `ifeq` and `else` branches of the same conditional. An alternative left
+/// without any condition means the rule is always defined, and then no
+/// condition is returned.
+fn collapse_complements(mut alternatives: Vec<Vec<Condition>>) -> Vec<Vec<Condition>> {
+    'search: loop {
+        for i in 0..alternatives.len() {
+            for j in i + 1..alternatives.len() {
+                let Some(k) = complement_position(&alternatives[i], &alternatives[j]) else {
+                    continue;
+                };
+
+                alternatives.remove(j);
+                let mut merged = alternatives.remove(i);
+                merged.remove(k);
+                if merged.is_empty() {
+                    return Vec::new();
+                }
+                if !alternatives.contains(&merged) {
+                    alternatives.push(merged);
+                }
+                continue 'search;
+            }
         }
+        return alternatives;
     }
+}
 
-    MakefileDoc { categories }
+fn complement_position(left: &[Condition], right: &[Condition]) -> Option<usize> {
+    if left.len() != right.len() {
+        return None;
+    }
+
+    let mut differing = left
+        .iter()
+        .zip(right)
+        .enumerate()
+        .filter(|(_, (l, r))| l != r);
+    let (k, (l, r)) = differing.next()?;
+    if differing.next().is_some() || l.negate() != *r {
+        return None;
+    }
+    Some(k)
 }
 
-pub fn clean_content(content: &str) -> Result<Vec<&str>, &'static str> {
+pub fn clean_content(content: &str) -> Result<Vec<(usize, &str)>, &'static str> {
     if content.trim().is_empty() {
         return Err("The Makefile is empty");
     }
 
-    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let lines: Vec<(usize, &str)> = content
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l))
+        .collect();
 
     Ok(lines)
 }
@@ -184,7 +745,8 @@ mod tests {
             deploy:
             "#;
 
-        let doc = parse(content);
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
         assert_eq!(doc.categories.len(), 1);
 
         let cat = &doc.categories[0];
@@ -381,4 +943,397 @@ mod tests {
         assert_eq!(db_cat.commands[0].name, "migrate");
         assert_eq!(db_cat.commands[1].name, "seed");
     }
+
+    #[test]
+    fn ignores_annotations_in_recipes_and_define_blocks() {
+        let content = "## @description Real target\nbuild:\n\techo foo: bar\n\t## @description Not a target\n\nfake-recipe:\ndefine TEMPLATE\n## @description Not a target either\nfake-define:\nendef\nifeq ($(OS),Windows_NT)\nCC := cl\nendif\n";
+
+        let doc = parse(content);
+        let commands = &doc.categories[0].commands;
+
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].name, "build");
+        assert_eq!(commands[0].description, "Real target");
+    }
+
+    #[test]
+    fn records_and_merges_conditional_branches() {
+        let content = r#"
+                ifeq ($(OS),Windows_NT)
+                ## @description Deploy from Windows
+                ## @env APP_KEY
+                deploy:
+                else
+                ## @description Deploy from Unix
+                ## @env APP_KEY, SSH_USER
+                deploy:
+                endif
+
+                ## @description Always there
+                build:
+            "#;
+
+        let doc = parse(content);
+        let commands = &doc.categories[0].commands;
+        assert_eq!(commands.len(), 2);
+
+        let deploy = &commands[0];
+        assert_eq!(deploy.name, "deploy");
+        let windows = Condition::Equal(String::from("OS"), String::from("Windows_NT"));
+        assert_eq!(
+            deploy.description,
+            r#"Deploy from Windows \n Deploy from Unix"#
+        );
+        assert_eq!(
+            deploy.branches,
+            vec![
+                Branch {
+                    conditions: vec![vec![windows.clone()]],
+                    description: String::from("Deploy from Windows"),
+                },
+                Branch {
+                    conditions: vec![vec![windows.negate()]],
+                    description: String::from("Deploy from Unix"),
+                },
+            ]
+        );
+        assert_eq!(deploy.env, vec!["APP_KEY", "SSH_USER"]);
+        assert!(deploy.conditions.is_empty());
+
+        assert!(commands[1].conditions.is_empty());
+        assert!(commands[1].branches.is_empty());
+    }
+
+    #[test]
+    fn conditions_are_not_parsed_back_from_text() {
+        let content = r#"
+                ifeq ($(MSG),a and b)
+                ## @description Hello (when x) world
+                deploy:
+                else
+                ## @description Hello (when x) world
+                deploy:
+                endif
+
+                ifeq ($(A),x!=y)
+                ## @description Only one branch
+                lint:
+                endif
+            "#;
+
+        let doc = parse(content);
+        let commands = &doc.categories[0].commands;
+
+        assert!(commands[0].conditions.is_empty());
+        assert!(commands[0].branches.is_empty());
+        assert_eq!(commands[0].description, "Hello (when x) world");
+        assert_eq!(
+            commands[1].conditions,
+            vec![vec![Condition::Equal(
+                String::from("A"),
+                String::from("x!=y")
+            )]]
+        );
+    }
+
+    #[test]
+    fn collapses_complementary_conditions() {
+        let ci = Condition::Defined(String::from("CI"));
+        let windows = Condition::Equal(String::from("OS"), String::from("Windows_NT"));
+        let debug = Condition::Equal(String::from("DEBUG"), String::from("1"));
+        let alternatives = vec![
+            vec![ci.clone(), windows.clone()],
+            vec![debug.clone()],
+            vec![ci.clone(), windows.negate()],
+        ];
+
+        assert_eq!(
+            collapse_complements(alternatives.clone()),
+            vec![vec![debug], vec![ci.clone()]]
+        );
+        assert_eq!(
+            collapse_complements(alternatives[1..].to_vec()),
+            &alternatives[1..]
+        );
+        assert!(collapse_complements(vec![vec![ci.clone()], vec![ci.negate()]]).is_empty());
+    }
+
+    #[test]
+    fn parses_pattern_and_static_pattern_rules() {
+        let content = r#"
+                ## @description Render a markdown file to PDF
+                ## @env PANDOC_FLAGS
+                %.pdf: %.md | build-dir
+
+                ## @description Compile the object files
+                $(OBJS): %.o: %.c
+
+                # Undocumented pattern
+                %.html: %.md
+
+                ## @description Regular target
+                docs:
+            "#;
+
+        let doc = parse(content);
+        assert_eq!(doc.categories[0].commands.len(), 1);
+        assert_eq!(doc.patterns.len(), 2);
+
+        let pdf = &doc.patterns[0];
+        assert_eq!(pdf.targets, None);
+        assert_eq!(pdf.pattern, "%.pdf");
+        assert_eq!(pdf.prerequisites, vec!["%.md", "build-dir"]);
+        assert_eq!(pdf.env, vec!["PANDOC_FLAGS"]);
+        assert!(pdf.matches("report.pdf"));
+        assert!(!pdf.matches(".pdf"));
+
+        let objs = &doc.patterns[1];
+        assert_eq!(objs.targets.as_deref(), Some("$(OBJS)"));
+        assert_eq!(objs.pattern, "%.o");
+        assert_eq!(objs.prerequisites, vec!["%.c"]);
+
+        assert_eq!(doc.targets, vec!["docs"]);
+    }
+
+    #[test]
+    fn pattern_specific_variables_are_not_pattern_rules() {
+        assert_eq!(try_extract_patterns("%.o: CFLAGS += -O2"), None);
+        assert_eq!(try_extract_patterns("%.o: CFLAGS := -O2"), None);
+        assert_eq!(
+            try_extract_patterns("$(OBJS): %.o: override CFLAGS = -O2"),
+            None
+        );
+        assert!(try_extract_patterns("%.o: %.c | build-dir").is_some());
+    }
+
+    #[test]
+    fn splits_grouped_pattern_targets() {
+        let content = r#"
+                ## @description Generate the parser
+                %.tab.c %.tab.h: %.y
+
+                ## @description Compile
+                ## @depends parse.tab.h
+                build:
+            "#;
+
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+        let patterns: Vec<&str> = doc.patterns.iter().map(|p| p.pattern.as_str()).collect();
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(patterns, vec!["%.tab.c", "%.tab.h"]);
+        assert!(doc.patterns[1].matches("parse.tab.h"));
+        assert_eq!(doc.patterns[1].prerequisites, vec!["%.y"]);
+        assert!(crate::validate::validate(&doc).is_empty());
+    }
+
+    #[test]
+    fn merges_double_colon_rules() {
+        let content = r#"
+                ## @description Remove build artifacts
+                clean::
+
+                ## @description Nothing to see
+                build:
+
+                ## @description Remove caches
+                ## @depends build
+                clean:: build
+            "#;
+
+        let doc = parse(content);
+        let commands = &doc.categories[0].commands;
+        assert_eq!(commands.len(), 2);
+        assert_eq!(doc.targets, vec!["clean", "build"]);
+
+        let clean = &commands[0];
+        assert_eq!(clean.name, "clean");
+        assert_eq!(
+            clean.description,
+            r#"Remove build artifacts \n Remove caches"#
+        );
+        assert_eq!(clean.dependencies, vec!["build"]);
+        assert_eq!(clean.prerequisites, vec!["build"]);
+    }
+
+    #[test]
+    fn captures_target_specific_variables() {
+        let content = r#"
+                deploy release: ENV = prod
+                deploy: export CFLAGS += -O2
+
+                ## @description Deploy to Production
+                deploy:
+            "#;
+
+        let doc = parse(content);
+        let deploy = &doc.categories[0].commands[0];
+
+        assert_eq!(deploy.name, "deploy");
+        assert_eq!(deploy.description, "Deploy to Production");
+        assert_eq!(
+            deploy
+                .variables
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>(),
+            vec!["ENV=prod", "CFLAGS+=-O2"]
+        );
+    }
+
+    #[test]
+    fn parse_with_custom_tag_syntax() {
+        let content = r#"
+                #:category Tools
+                #:description Format the code
+                #:depends install
+                ## @description Ignored with this syntax
+                fmt:
+            "#;
+
+        let config = Config {
+            comment_marker: String::from("#"),
+            tag_sigil: String::from(":"),
+            ..Config::default()
+        };
+        let doc = parse_with(content, &Tags::new(&config));
+
+        let cmd = &doc.categories[0].commands[0];
+        assert_eq!(doc.categories[0].name, "Tools");
+        assert_eq!(cmd.name, "fmt");
+        assert_eq!(cmd.description, "Format the code");
+        assert_eq!(cmd.dependencies, vec!["install"]);
+    }
+
+    #[test]
+    fn parse_custom_tags() {
+        let content = r#"
+                ## @description Deploy to Production
+                ## @owner platform-team
+                ## @links runbook, dashboard
+                ## @links alerts
+                deploy:
+            "#;
+
+        let config = Config::from_toml(
+            "[[tags]]\nname = \"owner\"\n[[tags]]\nname = \"links\"\nlist = true",
+        )
+        .unwrap();
+        let doc = parse_with(content, &Tags::new(&config));
+        let cmd = &doc.categories[0].commands[0];
+
+        assert_eq!(
+            cmd.tags.get("owner"),
+            Some(&TagValue::Text(String::from("platform-team")))
+        );
+        assert_eq!(
+            cmd.tags.get("links"),
+            Some(&TagValue::List(vec![
+                String::from("runbook"),
+                String::from("dashboard"),
+                String::from("alerts"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn param_is_a_builtin_list_tag() {
+        let content = "## @description Seed\n## @param VERSION the version, SEED\nseed:\n";
+
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            doc.categories[0].commands[0].tags.get(PARAM_TAG),
+            Some(&TagValue::List(vec![
+                String::from("VERSION the version"),
+                String::from("SEED"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn reports_unknown_tags_with_suggestions() {
+        let content = "## @descripton Typo\n## @depend up\n## @whatever x\ntypo:\n";
+
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+
+        assert!(doc.categories.is_empty());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "Warning (line 1): Unknown tag `@descripton`, did you mean `@description`?",
+                "Warning (line 2): Unknown tag `@depend`, did you mean `@depends`?",
+                "Warning (line 3): Unknown tag `@whatever`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_orphaned_annotations() {
+        let content = r#"
+                ## @description Separated from its target
+                VERSION := 1.0
+                separated:
+
+                ## @depends up
+                private:
+
+                ## @description Attached through a blank line
+
+                attached:
+
+                ## @description Lost at the end
+            "#;
+
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+
+        assert_eq!(doc.categories[0].commands.len(), 2);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.kind == DiagnosticKind::OrphanAnnotation)
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "Warning (line 2): These annotations are separated from 'separated' by unrelated lines (line 3)",
+                "Warning (line 6): These annotations are ignored because 'private' has no `@description`",
+                "Warning (line 13): These annotations are not attached to any rule",
+            ]
+        );
+    }
+
+    #[test]
+    fn special_targets_keep_the_annotations() {
+        let content = r#"
+                ## @description Build the app
+                .PHONY: build
+                build:
+
+                ## @description Run the tests
+                ## @depends build
+                .PHONY: test
+                .SILENT:
+                test:
+            "#;
+
+        let (doc, diagnostics) = parse_with_diagnostics(content, &Tags::default());
+        let diagnostics: Vec<_> = diagnostics
+            .into_iter()
+            .chain(crate::validate::validate(&doc))
+            .collect();
+
+        let commands = &doc.categories[0].commands;
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].description, "Build the app");
+        assert_eq!(commands[1].dependencies, vec!["build"]);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
 }
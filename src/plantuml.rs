@@ -0,0 +1,111 @@
+use crate::{
+    config::Config,
+    model::MakefileDoc,
+    render::Renderer,
+    workflow::{Node, NodeKind, WorkflowGraph},
+};
+
+/// The workflow graph as a PlantUML diagram, one dashed frame per category.
+pub struct PlantUmlRenderer;
+
+impl Renderer for PlantUmlRenderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String {
+        let graph = WorkflowGraph::build(doc, config);
+        let mut uml = String::new();
+
+        uml.push_str("@startuml\n");
+        uml.push_str("left to right direction\n");
+        uml.push_str("skinparam shadowing false\n");
+
+        for group in &graph.groups {
+            uml.push_str(&format!(
+                "\nrectangle {} as {} #line.dashed {{\n",
+                quote(group.label),
+                group.id
+            ));
+            for &idx in &group.nodes {
+                let node = &graph.nodes[idx];
+                uml.push_str(&format!(
+                    "  {} #{};line:{};line.bold;text:000000\n",
+                    element(node),
+                    group.colors.fill.trim_start_matches('#'),
+                    group.colors.stroke.trim_start_matches('#')
+                ));
+            }
+            uml.push_str("}\n");
+        }
+
+        uml.push('\n');
+        for node in graph.ungrouped() {
+            uml.push_str(&format!("{}\n", element(node)));
+        }
+        for &(from, to) in &graph.edges {
+            uml.push_str(&format!(
+                "{} --> {}\n",
+                graph.nodes[from].id, graph.nodes[to].id
+            ));
+        }
+
+        uml.push_str("@enduml\n");
+        uml
+    }
+}
+
+fn element(node: &Node) -> String {
+    let keyword = match node.kind {
+        NodeKind::Pattern(_) => "hexagon",
+        _ => "rectangle",
+    };
+    format!("{} {} as {}", keyword, quote(node.label), node.id)
+}
+
+/// PlantUML has no escape sequence for `"` inside a quoted name.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "<U+0022>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn renders_frames_and_escapes_quotes_in_labels() {
+        let doc = parse(
+            r#"
+                ## @category Build "all"
+                ## @description Build
+                ## @depends "vendor"
+                build:
+            "#,
+        );
+
+        let uml = PlantUmlRenderer.render(&doc, &Config::default());
+
+        assert!(uml.starts_with("@startuml\nleft to right direction\n"));
+        assert!(uml.contains(concat!(
+            "rectangle \"Build <U+0022>all<U+0022>\" as cat_Build_all #line.dashed {\n",
+            "  rectangle \"build\" as cmd_build #E1F5FE;line:01579B;line.bold;text:000000\n",
+            "}\n",
+        )));
+        assert!(uml.ends_with(concat!(
+            "rectangle \"<U+0022>vendor<U+0022>\" as cmd_vendor\n",
+            "cmd_build --> cmd_vendor\n",
+            "@enduml\n",
+        )));
+    }
+
+    #[test]
+    fn pattern_rules_are_hexagons() {
+        let doc = parse(
+            r#"
+                ## @description Compile a PDF
+                %.pdf: %.md
+            "#,
+        );
+
+        let uml = PlantUmlRenderer.render(&doc, &Config::default());
+
+        assert!(uml.contains("  hexagon \"%.pdf\" as pattern_0 #FAFAFA;line:616161;"));
+    }
+}
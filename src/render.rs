@@ -0,0 +1,99 @@
+use clap::ValueEnum;
+use std::path::Path;
+
+#[cfg(feature = "json")]
+use crate::json::JsonRenderer;
+use crate::{
+    completion::{CompletionRenderer, Shell},
+    config::Config,
+    d2::D2Renderer,
+    dot::DotRenderer,
+    generator::MarkdownRenderer,
+    html::HtmlRenderer,
+    man::{ManRenderer, page_name},
+    model::MakefileDoc,
+    plantuml::PlantUmlRenderer,
+    terminal::TerminalRenderer,
+};
+
+/// An output backend turning a parsed Makefile into a document.
+pub trait Renderer {
+    fn render(&self, doc: &MakefileDoc, config: &Config) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Format {
+    #[default]
+    Markdown,
+    Html,
+    Man,
+    Terminal,
+    Bash,
+    Zsh,
+    Fish,
+    Dot,
+    Plantuml,
+    D2,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+impl Format {
+    /// The renderer of the format, for a document written to `output`, or to
+    /// stdout when `None`.
+    pub fn renderer(self, output: Option<&Path>) -> Box<dyn Renderer> {
+        match self {
+            Format::Markdown => Box::new(MarkdownRenderer),
+            Format::Html => Box::new(HtmlRenderer),
+            Format::Man => Box::new(ManRenderer),
+            Format::Terminal => Box::new(TerminalRenderer::detect(output.is_none())),
+            Format::Bash => Box::new(CompletionRenderer { shell: Shell::Bash }),
+            Format::Zsh => Box::new(CompletionRenderer { shell: Shell::Zsh }),
+            Format::Fish => Box::new(CompletionRenderer { shell: Shell::Fish }),
+            Format::Dot => Box::new(DotRenderer),
+            Format::Plantuml => Box::new(PlantUmlRenderer),
+            Format::D2 => Box::new(D2Renderer),
+            #[cfg(feature = "json")]
+            Format::Json => Box::new(JsonRenderer),
+        }
+    }
+
+    /// The file written next to the Makefile when no output is given, or
+    /// `None` when the output goes to stdout.
+    pub fn default_file_name(self, config: &Config) -> Option<String> {
+        match self {
+            Format::Markdown => Some(String::from("MAKEFILE.md")),
+            Format::Html => Some(String::from("MAKEFILE.html")),
+            Format::Man => Some(format!("{}.7", page_name(config))),
+            Format::Dot => Some(String::from("MAKEFILE.dot")),
+            Format::Plantuml => Some(String::from("MAKEFILE.puml")),
+            Format::D2 => Some(String::from("MAKEFILE.d2")),
+            Format::Terminal | Format::Bash | Format::Zsh | Format::Fish => None,
+            #[cfg(feature = "json")]
+            Format::Json => Some(String::from("MAKEFILE.json")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generator::generate, parser::parse};
+
+    #[test]
+    fn markdown_format_uses_the_markdown_generator() {
+        let doc = parse(
+            r#"
+                ## @description Build
+                build:
+            "#,
+        );
+
+        let rendered = Format::Markdown
+            .renderer(None)
+            .render(&doc, &Config::default());
+
+        assert_eq!(rendered, generate(&doc));
+        assert_eq!(Format::default(), Format::Markdown);
+    }
+}
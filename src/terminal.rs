@@ -0,0 +1,170 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use terminal_size::{Width, terminal_size};
+
+use crate::{config::Config, model::MakefileDoc, render::Renderer};
+
+const DEFAULT_WIDTH: usize = 80;
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// A compact, category-grouped summary meant to be printed by `make help`.
+pub struct TerminalRenderer {
+    pub color: bool,
+    pub width: usize,
+}
+
+impl TerminalRenderer {
+    /// Colors are used only when the output goes to stdout, stdout is a
+    /// terminal and `NO_COLOR` is unset, and lines are wrapped to the width of
+    /// the terminal.
+    pub fn detect(to_stdout: bool) -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let width = match terminal_size() {
+            Some((Width(w), _)) => usize::from(w),
+            None => env::var("COLUMNS")
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(DEFAULT_WIDTH),
+        };
+
+        Self {
+            color: to_stdout && !no_color && io::stdout().is_terminal(),
+            width,
+        }
+    }
+
+    fn paint(&self, text: &str, style: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn render(&self, doc: &MakefileDoc, _config: &Config) -> String {
+        let name_width = doc
+            .commands()
+            .map(|cmd| cmd.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let indent = 2 + name_width + 2;
+        let desc_width = self.width.saturating_sub(indent).max(MIN_DESCRIPTION_WIDTH);
+
+        let mut out = String::new();
+        out.push_str(&format!("Usage: {}\n", self.paint("make <target>", BOLD)));
+
+        for cat in &doc.categories {
+            out.push_str(&format!(
+                "\n{}\n",
+                self.paint(&cat.name, &format!("{}{}", BOLD, YELLOW))
+            ));
+
+            for cmd in &cat.commands {
+                let padding = " ".repeat(name_width - cmd.name.chars().count());
+                let mut lines = wrap(&cmd.description, desc_width).into_iter();
+
+                out.push_str(&format!(
+                    "  {}{}  {}\n",
+                    self.paint(&cmd.name, CYAN),
+                    padding,
+                    lines.next().unwrap_or_default()
+                ));
+                for line in lines {
+                    out.push_str(&format!("{}{}\n", " ".repeat(indent), line));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Splits a description on its `\n` markers, then on word boundaries so that
+/// no line is longer than `width` characters (unless a single word is).
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.split("\\n") {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn wrap_breaks_on_words_and_line_markers() {
+        assert_eq!(
+            wrap("Build the whole app\\nThen test it", 10),
+            vec!["Build the", "whole app", "Then test", "it"]
+        );
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn aligns_commands_by_category() {
+        let doc = parse(
+            r#"
+                ## @category Database
+                ## @description Run the migrations on the database
+                migrate:
+
+                ## @description Seed
+                seed:
+            "#,
+        );
+        let renderer = TerminalRenderer {
+            color: false,
+            width: 32,
+        };
+
+        assert_eq!(
+            renderer.render(&doc, &Config::default()),
+            concat!(
+                "Usage: make <target>\n",
+                "\n",
+                "Database\n",
+                "  migrate  Run the migrations on\n",
+                "           the database\n",
+                "  seed     Seed\n",
+            )
+        );
+    }
+
+    #[test]
+    fn colors_names_only_when_enabled() {
+        let doc = parse("## @description Build\nbuild:");
+        let renderer = TerminalRenderer {
+            color: true,
+            width: 80,
+        };
+
+        assert!(
+            renderer
+                .render(&doc, &Config::default())
+                .contains("  \x1b[36mbuild\x1b[0m  Build\n")
+        );
+    }
+}
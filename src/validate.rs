@@ -0,0 +1,146 @@
+use crate::{
+    diagnostic::{Diagnostic, DiagnosticKind, suggest},
+    graph::DependencyGraph,
+    model::MakefileDoc,
+};
+
+pub fn validate(doc: &MakefileDoc) -> Vec<Diagnostic> {
+    let mut diagnostics = check_dependencies(doc);
+    diagnostics.extend(check_cycles(doc));
+    diagnostics
+}
+
+fn check_cycles(doc: &MakefileDoc) -> Vec<Diagnostic> {
+    DependencyGraph::build(doc)
+        .cycles()
+        .into_iter()
+        .map(|cycle| {
+            let path = cycle
+                .iter()
+                .chain(cycle.first())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            Diagnostic::warning(
+                DiagnosticKind::DependencyCycle,
+                None,
+                format!("Dependency cycle detected: {}", path),
+            )
+        })
+        .collect()
+}
+
+fn check_dependencies(doc: &MakefileDoc) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let targets: Vec<&str> = doc.targets.iter().map(String::as_str).collect();
+
+    for cmd in doc.commands() {
+        for dep in &cmd.dependencies {
+            if dep.contains('$') || doc.find_command(dep).is_some() {
+                continue;
+            }
+
+            if targets.contains(&dep.as_str()) {
+                diagnostics.push(Diagnostic::warning(
+                    DiagnosticKind::UndocumentedDependency,
+                    None,
+                    format!(
+                        "'{}' depends on '{}', which exists but is not documented",
+                        cmd.name, dep
+                    ),
+                ));
+                continue;
+            }
+
+            if doc.patterns.iter().any(|p| p.matches(dep)) {
+                continue;
+            }
+
+            let message = match suggest(dep, &targets) {
+                Some(known) => format!(
+                    "'{}' depends on '{}', which is not a target of this Makefile, did you mean '{}'?",
+                    cmd.name, dep, known
+                ),
+                None => format!(
+                    "'{}' depends on '{}', which is not a target of this Makefile",
+                    cmd.name, dep
+                ),
+            };
+            diagnostics.push(Diagnostic::warning(
+                DiagnosticKind::UnknownDependency,
+                None,
+                message,
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn reports_unknown_and_undocumented_dependencies() {
+        let content = r#"
+                ## @description Build the assets
+                build:
+
+                ## @description Deploy to Production
+                ## @depends biuld, migrate, report.pdf, $(EXTRA), typo
+                deploy:
+
+                migrate:
+
+                ## @description Render a markdown file to PDF
+                %.pdf: %.md
+            "#;
+
+        let diagnostics = validate(&parse(content));
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.kind, d.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    DiagnosticKind::UnknownDependency,
+                    "'deploy' depends on 'biuld', which is not a target of this Makefile, did you mean 'build'?"
+                ),
+                (
+                    DiagnosticKind::UndocumentedDependency,
+                    "'deploy' depends on 'migrate', which exists but is not documented"
+                ),
+                (
+                    DiagnosticKind::UnknownDependency,
+                    "'deploy' depends on 'typo', which is not a target of this Makefile"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_dependency_cycles() {
+        let content = r#"
+                ## @description A
+                ## @depends b
+                a:
+
+                ## @description B
+                ## @depends a
+                b:
+            "#;
+
+        let diagnostics = validate(&parse(content));
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::DependencyCycle);
+        assert_eq!(
+            diagnostics[0].message,
+            "Dependency cycle detected: a -> b -> a"
+        );
+    }
+}
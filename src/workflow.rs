@@ -0,0 +1,389 @@
+use crate::{config::Config, graph::transitive_reduction, mermaid::MermaidIds, model::MakefileDoc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Colors {
+    pub fill: &'static str,
+    pub stroke: &'static str,
+}
+
+/// Colours of the categories, in order, starting over after the last one.
+pub const PALETTE: [Colors; 6] = [
+    Colors {
+        fill: "#E1F5FE",
+        stroke: "#01579B",
+    },
+    Colors {
+        fill: "#E8F5E9",
+        stroke: "#1B5E20",
+    },
+    Colors {
+        fill: "#FFF3E0",
+        stroke: "#E65100",
+    },
+    Colors {
+        fill: "#F3E5F5",
+        stroke: "#4A148C",
+    },
+    Colors {
+        fill: "#FFEBEE",
+        stroke: "#B71C1C",
+    },
+    Colors {
+        fill: "#ECEFF1",
+        stroke: "#263238",
+    },
+];
+
+pub const PATTERN_COLORS: Colors = Colors {
+    fill: "#FAFAFA",
+    stroke: "#616161",
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    Command,
+    /// The index of the rule in `MakefileDoc::patterns`.
+    Pattern(usize),
+    /// A dependency that is neither documented nor built by a pattern rule.
+    Undocumented,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    pub id: String,
+    pub label: &'a str,
+    pub kind: NodeKind,
+    /// Index of the group in `WorkflowGraph::groups`, if any.
+    pub group: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group<'a> {
+    pub id: String,
+    pub label: &'a str,
+    pub colors: Colors,
+    pub nodes: Vec<usize>,
+}
+
+/// The workflow graph shared by every graph backend: one group per category
+/// (plus one for the pattern rules), and an edge from each command or pattern
+/// rule to its `@depends`. Ids are the Mermaid ones, which are also valid
+/// PlantUML aliases, D2 keys and Graphviz ids.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowGraph<'a> {
+    pub groups: Vec<Group<'a>>,
+    pub nodes: Vec<Node<'a>>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl<'a> WorkflowGraph<'a> {
+    pub fn build(doc: &'a MakefileDoc, config: &Config) -> Self {
+        let ids = MermaidIds::build(doc);
+        let mut graph = Self {
+            groups: Vec::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for (i, cat) in doc.categories.iter().enumerate() {
+            let group = graph.groups.len();
+            let nodes = cat
+                .commands
+                .iter()
+                .map(|cmd| {
+                    graph.push_node(
+                        ids.node_id(&cmd.name),
+                        &cmd.name,
+                        NodeKind::Command,
+                        Some(group),
+                    )
+                })
+                .collect();
+
+            graph.groups.push(Group {
+                id: ids.category_id(i).to_string(),
+                label: &cat.name,
+                colors: PALETTE[i % PALETTE.len()],
+                nodes,
+            });
+        }
+
+        if !doc.patterns.is_empty() {
+            let group = graph.groups.len();
+            let nodes = doc
+                .patterns
+                .iter()
+                .enumerate()
+                .map(|(i, pattern)| {
+                    graph.push_node(
+                        ids.pattern_id(i),
+                        &pattern.pattern,
+                        NodeKind::Pattern(i),
+                        Some(group),
+                    )
+                })
+                .collect();
+
+            graph.groups.push(Group {
+                id: String::from("pattern_rules"),
+                label: "Pattern Rules",
+                colors: PATTERN_COLORS,
+                nodes,
+            });
+        }
+
+        let mut edges = Vec::new();
+        for cmd in doc.commands() {
+            let from = graph.command_node(&cmd.name);
+            for dep in &cmd.dependencies {
+                edges.push((from, graph.dependency_node(doc, &ids, dep)));
+            }
+        }
+        for (i, pattern) in doc.patterns.iter().enumerate() {
+            let from = graph.pattern_node(i);
+            for dep in &pattern.dependencies {
+                edges.push((from, graph.dependency_node(doc, &ids, dep)));
+            }
+        }
+
+        graph.edges = if config.transitive_reduction {
+            transitive_reduction(&edges)
+        } else {
+            edges
+        };
+        graph
+    }
+
+    fn push_node(
+        &mut self,
+        id: &str,
+        label: &'a str,
+        kind: NodeKind,
+        group: Option<usize>,
+    ) -> usize {
+        self.nodes.push(Node {
+            id: id.to_string(),
+            label,
+            kind,
+            group,
+        });
+        self.nodes.len() - 1
+    }
+
+    /// The node of the command called `name`, if it is documented.
+    pub fn find_command(&self, name: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|n| n.kind == NodeKind::Command && n.label == name)
+    }
+
+    /// The part of the graph within `depth` edges of `node`, upstream
+    /// (dependents) and downstream (dependencies). Nodes keep their id, kind
+    /// and group, and groups their colours, so that it renders as a piece of
+    /// the whole graph.
+    pub fn neighborhood(&self, node: usize, depth: usize) -> Self {
+        let mut kept = vec![node];
+        let mut kept_edges = Vec::new();
+
+        for upstream in [false, true] {
+            let mut frontier = vec![node];
+            for _ in 0..depth {
+                let mut next_frontier = Vec::new();
+                for (i, &(from, to)) in self.edges.iter().enumerate() {
+                    let (near, far) = if upstream { (to, from) } else { (from, to) };
+                    if !frontier.contains(&near) {
+                        continue;
+                    }
+                    if !kept_edges.contains(&i) {
+                        kept_edges.push(i);
+                    }
+                    if !kept.contains(&far) {
+                        kept.push(far);
+                        next_frontier.push(far);
+                    }
+                }
+                frontier = next_frontier;
+            }
+        }
+
+        let mut index = vec![None; self.nodes.len()];
+        let mut nodes = Vec::new();
+        for (i, n) in self.nodes.iter().enumerate() {
+            if kept.contains(&i) {
+                index[i] = Some(nodes.len());
+                nodes.push(n.clone());
+            }
+        }
+
+        let groups = self
+            .groups
+            .iter()
+            .map(|group| Group {
+                nodes: group.nodes.iter().filter_map(|&i| index[i]).collect(),
+                ..group.clone()
+            })
+            .collect();
+
+        let edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| kept_edges.contains(i))
+            .filter_map(|(_, &(from, to))| Some((index[from]?, index[to]?)))
+            .collect();
+
+        Self {
+            groups,
+            nodes,
+            edges,
+        }
+    }
+
+    fn command_node(&self, name: &str) -> usize {
+        self.find_command(name).expect("every command has a node")
+    }
+
+    fn pattern_node(&self, pattern_idx: usize) -> usize {
+        self.nodes
+            .iter()
+            .position(|n| n.kind == NodeKind::Pattern(pattern_idx))
+            .expect("every pattern rule has a node")
+    }
+
+    fn dependency_node(&mut self, doc: &'a MakefileDoc, ids: &MermaidIds, dep: &'a str) -> usize {
+        if doc.find_command(dep).is_some() {
+            return self.command_node(dep);
+        }
+        if let Some(i) = doc.patterns.iter().position(|p| p.matches(dep)) {
+            return self.pattern_node(i);
+        }
+
+        match self
+            .nodes
+            .iter()
+            .position(|n| n.kind == NodeKind::Undocumented && n.label == dep)
+        {
+            Some(idx) => idx,
+            None => self.push_node(ids.node_id(dep), dep, NodeKind::Undocumented, None),
+        }
+    }
+
+    /// The nodes that belong to no group, in order of appearance.
+    pub fn ungrouped(&self) -> impl Iterator<Item = &Node<'a>> {
+        self.nodes.iter().filter(|n| n.group.is_none())
+    }
+}
+
+/// Quotes a label for the backends that escape with a backslash (Graphviz
+/// and D2).
+pub fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn groups_nodes_and_links_dependencies() {
+        let doc = parse(
+            r#"
+                ## @category Build
+                ## @description Build
+                ## @depends install, docs.pdf, vendor
+                build:
+
+                ## @category Setup
+                ## @description Install
+                install:
+
+                ## @description Compile a PDF
+                %.pdf: %.md
+            "#,
+        );
+
+        let graph = WorkflowGraph::build(&doc, &Config::default());
+        let labels = |nodes: &[usize]| {
+            nodes
+                .iter()
+                .map(|&i| graph.nodes[i].label)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(graph.groups.len(), 3);
+        assert_eq!(graph.groups[1].id, "cat_Setup");
+        assert_eq!(graph.groups[1].colors, PALETTE[1]);
+        assert_eq!(labels(&graph.groups[2].nodes), vec!["%.pdf"]);
+        assert_eq!(graph.groups[2].colors, PATTERN_COLORS);
+
+        let ungrouped: Vec<&str> = graph.ungrouped().map(|n| n.id.as_str()).collect();
+        assert_eq!(ungrouped, vec!["cmd_vendor"]);
+
+        let edges: Vec<(&str, &str)> = graph
+            .edges
+            .iter()
+            .map(|&(from, to)| (graph.nodes[from].label, graph.nodes[to].label))
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                ("build", "install"),
+                ("build", "%.pdf"),
+                ("build", "vendor")
+            ]
+        );
+    }
+
+    #[test]
+    fn neighborhood_is_limited_by_depth() {
+        let doc = parse(
+            r#"
+                ## @category Release
+                ## @description Release
+                ## @depends deploy
+                release:
+
+                ## @description Deploy
+                ## @depends build
+                deploy:
+
+                ## @category Build
+                ## @description Build
+                ## @depends install
+                build: gen.c
+
+                ## @description Unrelated
+                lint:
+            "#,
+        );
+
+        let graph = WorkflowGraph::build(&doc, &Config::default());
+        let deploy = graph.find_command("deploy").unwrap();
+        let labels = |graph: &WorkflowGraph| {
+            graph
+                .nodes
+                .iter()
+                .map(|n| n.label.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let focused = graph.neighborhood(deploy, 1);
+        assert_eq!(labels(&focused), vec!["release", "deploy", "build"]);
+        assert_eq!(focused.edges, vec![(0, 1), (1, 2)]);
+        assert_eq!(focused.groups[1].nodes, vec![2]);
+        assert_eq!(focused.groups[1].colors, PALETTE[1]);
+
+        let focused = graph.neighborhood(deploy, 2);
+        assert_eq!(
+            labels(&focused),
+            vec!["release", "deploy", "build", "install"]
+        );
+        assert_eq!(focused.edges.len(), 3);
+    }
+
+    #[test]
+    fn quote_escapes_backslashes_and_quotes() {
+        assert_eq!(quote(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+}